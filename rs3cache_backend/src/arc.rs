@@ -31,6 +31,8 @@ pub struct Archive {
     pub(crate) files: BTreeMap<u32, Bytes>,
     #[cfg(feature = "dat")]
     pub(crate) files_named: BTreeMap<i32, Bytes>,
+    #[cfg(any(feature = "sqlite", feature = "dat2"))]
+    pub(crate) file_ids_named: BTreeMap<i32, u32>,
 }
 
 impl std::fmt::Debug for Archive {
@@ -103,7 +105,17 @@ impl Archive {
             }
         };
 
-        Archive { index_id, archive_id, files }
+        let file_ids_named = match metadata.child_names() {
+            Some(names) => izip!(names.iter().copied(), metadata.child_indices().iter().copied()).collect(),
+            None => BTreeMap::new(),
+        };
+
+        Archive {
+            index_id,
+            archive_id,
+            files,
+            file_ids_named,
+        }
     }
 
     /// Removes and returns a File.
@@ -118,6 +130,22 @@ impl Archive {
             .cloned()
     }
 
+    /// Returns the file whose name hashes to the same value as `name`.
+    ///
+    /// # Errors
+    ///
+    /// Raises [`FileMissingError`](crate::error::CacheErrorKind::FileMissingError) with the hash of `name` as file id,
+    /// if the archive has no file by that name or its files are not named.
+    #[cfg(any(feature = "sqlite", feature = "dat2"))]
+    pub fn file_named(&self, name: impl AsRef<str>) -> CacheResult<Bytes> {
+        let hash = crate::hash::hash_djb2(name.as_ref());
+
+        self.file_ids_named
+            .get(&hash)
+            .ok_or_else(|| CacheError::file_missing(self.index_id(), self.archive_id(), hash as u32))
+            .and_then(|file_id| self.file(file_id))
+    }
+
     /// Take the files. Consumes the [`Archive`].
     ///
     /// # Panics
@@ -127,6 +155,16 @@ impl Archive {
         self.files
     }
 
+    /// Consumes the [`Archive`], yielding its files in ascending order of their id.
+    ///
+    /// This does not decode anything lazily: an archive is decompressed and split into files
+    /// as a whole when it is read, and its files are slices of that one buffer.
+    /// The buffer is freed once every file yielded by this iterator has been dropped,
+    /// so memory use is bounded by the largest archive rather than by the whole index.
+    pub fn files_iter(self) -> impl Iterator<Item = (u32, Bytes)> {
+        self.files.into_iter()
+    }
+
     /// The quantity of files currently in the archive.
     pub fn file_count(&self) -> usize {
         self.files.len()
@@ -249,3 +287,31 @@ impl Archive {
         Ok(format!("Archive({}, {})", self.index_id(), self.archive_id()))
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod archive_tests {
+    use super::*;
+    use crate::hash::hash_djb2;
+
+    #[test]
+    fn file_named() {
+        let metadata = Metadata {
+            child_count: 2,
+            child_indices: vec![3, 7],
+            child_names: Some(vec![hash_djb2("first"), hash_djb2("second")]),
+            ..Default::default()
+        };
+
+        // One byte of format, three offsets and two single byte files.
+        let mut data = vec![1];
+        for offset in [13_i32, 14, 15] {
+            data.extend(offset.to_be_bytes());
+        }
+        data.extend([b'a', b'b']);
+
+        let archive = Archive::deserialize(&metadata, Bytes::from(data));
+        assert_eq!(archive.file_named("second").unwrap(), Bytes::from_static(b"b"));
+        assert_eq!(archive.file_named("first").unwrap(), archive.file(&3).unwrap());
+        assert!(archive.file_named("third").is_err());
+    }
+}
@@ -10,7 +10,7 @@ use std::{
 };
 
 use bytes::{Buf, Bytes};
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::error::CacheError;
 
@@ -34,6 +34,14 @@ impl ReadError {
             kind: Kind::Error(ReadErrorKind::NotNulTerminated),
         }
     }
+    /// For values that do not fit the integer they are read as.
+    #[track_caller]
+    pub fn overflow() -> Self {
+        Self {
+            location: Location::caller(),
+            kind: Kind::Error(ReadErrorKind::Overflow),
+        }
+    }
     #[track_caller]
     pub fn opcode_not_implemented(opcode: u8) -> Self {
         Self {
@@ -42,6 +50,15 @@ impl ReadError {
         }
     }
 
+    /// Like [`opcode_not_implemented`](ReadError::opcode_not_implemented), for an opcode that is only decoded with `feature` enabled.
+    #[track_caller]
+    pub fn opcode_needs_feature(opcode: u8, feature: &'static str) -> Self {
+        Self {
+            location: Location::caller(),
+            kind: Kind::Error(ReadErrorKind::OpcodeNeedsFeature(opcode, feature)),
+        }
+    }
+
     #[track_caller]
     pub fn not_exhausted() -> Self {
         Self {
@@ -102,7 +119,9 @@ pub enum ReadErrorKind {
     Eof,
     NotNulTerminated,
     NotExhausted,
+    Overflow,
     OpcodeNotImplemented(u8),
+    OpcodeNeedsFeature(u8, &'static str),
     #[cfg(debug_assertions)]
     DuplicateOpcode(Vec<u8>, u8),
 }
@@ -117,9 +136,15 @@ impl Display for ReadError {
         match &self.kind {
             Error(Eof) => writeln!(f, "Unexpected end of file ({location})")?,
             Error(NotNulTerminated) => writeln!(f, "Buffer did not contain nul terminator")?,
+            Error(Overflow) => writeln!(f, "Read a value that overflows its integer type ({location})")?,
             Error(OpcodeNotImplemented(opcode)) => {
                 writeln!(f, "Read opcode {opcode}, but decoding opcode {opcode} is not implemented. ({location})")?
             }
+            Error(OpcodeNeedsFeature(opcode, feature)) => writeln!(
+                f,
+                "Read opcode {opcode}, but opcode {opcode} is only decoded with the `{feature}` feature enabled, \
+                 which suggests the cache is newer than the enabled features. ({location})"
+            )?,
             Error(NotExhausted) => writeln!(f, "Reached terminating opcode but the buffer was not exhausted ({location})")?,
             #[cfg(debug_assertions)]
             Error(DuplicateOpcode(_, opcode)) => writeln!(f, "Read opcode {opcode}, but opcode {opcode} was already decoded. ({location})")?,
@@ -224,7 +249,7 @@ pub trait BufExtra: Buf + Sized + Clone {
     }
     #[inline]
     fn try_get_array<const LENGTH: usize>(&mut self) -> Result<[u8; LENGTH], ReadError> {
-        if self.remaining() >= LENGTH {
+        if self.remaining() < LENGTH {
             Err(ReadError::eof())
         } else {
             let mut dst = [0; LENGTH];
@@ -319,6 +344,21 @@ pub trait BufExtra: Buf + Sized + Clone {
         result
     }
 
+    /// Reads a multiple of two bytes as an 32-bit unsigned integer.
+    ///
+    /// Returns an [`overflow`](ReadError::overflow) error if the value does not fit in a `u32`.
+    #[track_caller]
+    #[inline]
+    fn try_get_smarts(&mut self) -> Result<u32, ReadError> {
+        let mut value: u32 = 0;
+        loop {
+            match self.try_get_unsigned_smart()? as u32 {
+                0x7FFF => value = value.checked_add(0x7FFF).ok_or_else(ReadError::overflow)?,
+                offset => break value.checked_add(offset).ok_or_else(ReadError::overflow),
+            }
+        }
+    }
+
     /// Reads a multiple of two bytes as an 32-bit unsigned integer.
     #[inline]
     fn get_smarts(&mut self) -> u32 {
@@ -331,6 +371,22 @@ pub trait BufExtra: Buf + Sized + Clone {
         }
     }
 
+    /// Reads one byte, returning 8 boolean bitflags.
+    #[inline]
+    fn try_get_bitflags(&mut self) -> Result<[bool; 8], ReadError> {
+        let flags = self.try_get_u8()?;
+        Ok([
+            flags & 0x1 != 0,
+            flags & 0x2 != 0,
+            flags & 0x4 != 0,
+            flags & 0x8 != 0,
+            flags & 0x10 != 0,
+            flags & 0x20 != 0,
+            flags & 0x40 != 0,
+            flags & 0x80 != 0,
+        ])
+    }
+
     /// Reads one byte, returning 8 boolean bitflags.
     #[inline]
     fn get_bitflags(&mut self) -> [bool; 8] {
@@ -386,6 +442,57 @@ pub trait BufExtra: Buf + Sized + Clone {
         self.get_array()
     }
 
+    /// Reads four unsigned bytes, returning a `[red, green, blue, alpha]` array.
+    #[inline]
+    fn get_rgba(&mut self) -> [u8; 4] {
+        self.get_array()
+    }
+
+    /// Reads a colour packed into two bytes as hue, saturation and lightness, returning it as `[red, green, blue]`.
+    ///
+    /// See [`hsl16_to_rgb`].
+    #[inline]
+    fn get_hsl16(&mut self) -> [u8; 3] {
+        hsl16_to_rgb(self.get_u16())
+    }
+
+    /// Reads a colour packed into two bytes as five bits of red, six of green and five of blue,
+    /// returning it as `[red, green, blue]`.
+    ///
+    /// See [`rgb565_to_rgb`].
+    #[inline]
+    fn get_packed_rgb565(&mut self) -> [u8; 3] {
+        rgb565_to_rgb(self.get_u16())
+    }
+
+    /// Like [`get_rgb`](BufExtra::get_rgb), but returns an error if there are not enough bytes remaining.
+    #[track_caller]
+    #[inline]
+    fn try_get_rgb(&mut self) -> Result<[u8; 3], ReadError> {
+        self.try_get_array()
+    }
+
+    /// Like [`get_rgba`](BufExtra::get_rgba), but returns an error if there are not enough bytes remaining.
+    #[track_caller]
+    #[inline]
+    fn try_get_rgba(&mut self) -> Result<[u8; 4], ReadError> {
+        self.try_get_array()
+    }
+
+    /// Like [`get_hsl16`](BufExtra::get_hsl16), but returns an error if there are not enough bytes remaining.
+    #[track_caller]
+    #[inline]
+    fn try_get_hsl16(&mut self) -> Result<[u8; 3], ReadError> {
+        self.try_get_u16().map(hsl16_to_rgb)
+    }
+
+    /// Like [`get_packed_rgb565`](BufExtra::get_packed_rgb565), but returns an error if there are not enough bytes remaining.
+    #[track_caller]
+    #[inline]
+    fn try_get_packed_rgb565(&mut self) -> Result<[u8; 3], ReadError> {
+        self.try_get_u16().map(rgb565_to_rgb)
+    }
+
     /// Reads two obfuscated bytes.
     #[inline]
     fn try_get_masked_index(&mut self) -> Result<u16, ReadError> {
@@ -403,6 +510,42 @@ pub trait BufExtra: Buf + Sized + Clone {
 
 impl<T: Buf + Clone> BufExtra for T {}
 
+/// Converts a colour packed as five bits of red, six of green and five of blue to `[red, green, blue]`.
+pub fn rgb565_to_rgb(rgb: u16) -> [u8; 3] {
+    let (red, green, blue) = ((rgb >> 11) as u8, ((rgb >> 5) & 0x3F) as u8, (rgb & 0x1F) as u8);
+    [red << 3 | red >> 2, green << 2 | green >> 4, blue << 3 | blue >> 2]
+}
+
+/// Converts a colour packed as six bits of hue, three of saturation and seven of lightness to `[red, green, blue]`.
+pub fn hsl16_to_rgb(hsl: u16) -> [u8; 3] {
+    let hue = ((hsl >> 10) & 0x3F) as f64 / 64.0 + 1.0 / 128.0;
+    let saturation = ((hsl >> 7) & 0x07) as f64 / 8.0 + 1.0 / 16.0;
+    let lightness = (hsl & 0x7F) as f64 / 128.0;
+
+    let q = if lightness < 0.5 {
+        lightness * (1.0 + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2.0 * lightness - q;
+
+    let channel = |t: f64| {
+        let t = t.rem_euclid(1.0);
+        let value = if 6.0 * t < 1.0 {
+            p + (q - p) * 6.0 * t
+        } else if 2.0 * t < 1.0 {
+            q
+        } else if 3.0 * t < 2.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        };
+        (value * 256.0).clamp(0.0, 255.0) as u8
+    };
+
+    [channel(hue + 1.0 / 3.0), channel(hue), channel(hue - 1.0 / 3.0)]
+}
+
 #[derive(Clone, Debug)]
 pub struct JString<R: Buf> {
     inner: JStringKind<R>,
@@ -418,6 +561,16 @@ impl<R: Buf> Serialize for JString<R> {
     }
 }
 
+impl<'de, R: Buf> Deserialize<'de> for JString<R> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum JStringKind<R: Buf> {
     Refcounted { buf: R, len: usize },
@@ -480,3 +633,73 @@ impl<R: Buf> pyo3::IntoPy<pyo3::Py<pyo3::PyAny>> for JString<R> {
         pyo3::types::PyString::new(py, &self).into()
     }
 }
+
+#[cfg(test)]
+mod colour_tests {
+    use super::*;
+
+    #[test]
+    fn rgb565() {
+        let mut buf = Bytes::from_static(&[0xF8, 0x00, 0x07, 0xE0, 0x00, 0x1F]);
+        assert_eq!(buf.get_packed_rgb565(), [255, 0, 0]);
+        assert_eq!(buf.get_packed_rgb565(), [0, 255, 0]);
+        assert_eq!(buf.get_packed_rgb565(), [0, 0, 255]);
+    }
+
+    #[test]
+    fn try_colours() {
+        let mut buf = Bytes::from_static(&[1, 2, 3, 4, 5]);
+        assert_eq!(buf.try_get_rgba().unwrap(), [1, 2, 3, 4]);
+        assert!(buf.try_get_packed_rgb565().is_err());
+
+        let mut buf = Bytes::from_static(&[0xF8, 0x00, 0x07]);
+        assert_eq!(buf.try_get_packed_rgb565().unwrap(), [255, 0, 0]);
+        assert!(buf.try_get_rgb().is_err());
+        assert!(buf.try_get_hsl16().is_err());
+    }
+
+    #[test]
+    fn hsl16() {
+        assert_eq!(hsl16_to_rgb(0), [0, 0, 0]);
+        assert!(hsl16_to_rgb(0x7F).iter().all(|&channel| channel > 250));
+
+        // Fully saturated, half lightness, zero hue is mostly red.
+        let [red, green, blue] = hsl16_to_rgb(0x07 << 7 | 0x40);
+        assert!(red > 200 && green < 50 && blue < 50, "{:?}", [red, green, blue]);
+    }
+}
+
+#[cfg(test)]
+mod array_tests {
+    use super::*;
+
+    #[test]
+    fn try_get_array_bounds() {
+        let mut buf = Bytes::from_static(&[1, 2, 3, 4, 5]);
+        assert_eq!(buf.try_get_array::<4>().unwrap(), [1, 2, 3, 4]);
+        assert!(buf.try_get_array::<2>().is_err());
+        // A failed read consumes nothing.
+        assert_eq!(buf.try_get_array::<1>().unwrap(), [5]);
+        assert_eq!(buf.try_get_array::<0>().unwrap(), [0u8; 0]);
+    }
+}
+
+#[cfg(test)]
+mod smart_tests {
+    use super::*;
+
+    #[test]
+    fn try_get_smarts() {
+        let mut buf = Bytes::from_static(&[0xFF, 0xFF, 0x05]);
+        assert_eq!(buf.try_get_smarts().unwrap(), 0x7FFF + 5);
+    }
+
+    #[test]
+    fn try_get_smarts_overflow() {
+        // Enough continuation smarts of 0x7FFF to exceed u32::MAX.
+        let data: Vec<u8> = iter::repeat([0xFF, 0xFF]).take(0x20005).flatten().chain([0x00]).collect();
+        let mut buf = Bytes::from(data);
+        let error = buf.try_get_smarts().unwrap_err();
+        assert!(matches!(error.kind, Kind::Error(ReadErrorKind::Overflow)), "{error}");
+    }
+}
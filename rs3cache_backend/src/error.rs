@@ -6,7 +6,11 @@ use std::{
     sync::Arc,
 };
 
-use crate::{buf::ReadError, decoder::DecodeError, index::CachePath};
+use crate::{
+    buf::ReadError,
+    decoder::DecodeError,
+    index::{CachePath, PathOrigin},
+};
 /// Result wrapper for [`CacheError`].
 pub type CacheResult<T> = Result<T, CacheError>;
 
@@ -38,6 +42,18 @@ impl CacheError {
         }
     }
 
+    /// For archives that are looked up by name, such as old school mapsquares.
+    #[track_caller]
+    pub fn named_archive_missing(index: u32, name: impl Into<String>, name_hash: i32) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                kind: CacheErrorKind::NamedArchiveNotFoundError(index, name.into(), name_hash),
+                backtrace: Backtrace::capture(),
+                location: Location::caller(),
+            }),
+        }
+    }
+
     #[track_caller]
     pub fn file_missing(index: u32, archive: u32, file: u32) -> Self {
         Self {
@@ -93,6 +109,18 @@ impl CacheError {
         }
     }
 
+    /// For images that fail to encode or save for reasons other than io.
+    #[track_caller]
+    pub fn image(cause: impl Display, path: PathBuf) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                kind: CacheErrorKind::ImageError(cause.to_string(), path),
+                backtrace: Backtrace::capture(),
+                location: Location::caller(),
+            }),
+        }
+    }
+
     #[cfg(feature = "dat2")]
     pub fn xtea_load_error(cause: serde_json::Error, path: PathBuf) -> Self {
         Self {
@@ -104,6 +132,28 @@ impl CacheError {
         }
     }
 
+    #[track_caller]
+    pub fn sector(index: u32, archive: u32, sector: u32) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                kind: CacheErrorKind::SectorError(index, archive, sector),
+                backtrace: Backtrace::capture(),
+                location: Location::caller(),
+            }),
+        }
+    }
+
+    #[track_caller]
+    pub fn plane(plane: u8) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                kind: CacheErrorKind::PlaneError(plane),
+                backtrace: Backtrace::capture(),
+                location: Location::caller(),
+            }),
+        }
+    }
+
     #[cfg(feature = "dat2")]
     pub fn xtea_absent(i: u8, j: u8) -> Self {
         Self {
@@ -137,10 +187,20 @@ pub enum CacheErrorKind {
     CacheNotFoundError(std::io::Error, PathBuf, Arc<CachePath>),
     /// Raised if an [`Archive`](crate::arc::Archive) is not in the [`CacheIndex`](crate::index::CacheIndex).
     ArchiveNotFoundError(u32, u32),
+    /// Raised if no [`Archive`](crate::arc::Archive) in the [`CacheIndex`](crate::index::CacheIndex) has the given name.
+    /// Holds the name and its hash.
+    NamedArchiveNotFoundError(u32, String, i32),
     /// Raised if a file is not in an [`Archive`](crate::arc::Archive).
     FileMissingError(u32, u32, u32),
     /// Raised if reading from a buffer fails
     ReadError(ReadError),
+    /// Raised if the chain of sectors an archive is stored in is malformed,
+    /// such as a sector belonging to another archive or a chain that loops back on itself.
+    SectorError(u32, u32, u32),
+    /// Raised if a plane is not in the range `0..4`.
+    PlaneError(u8),
+    /// Raised if an image cannot be encoded or saved to the given path.
+    ImageError(String, PathBuf),
     /// ZIf this is raised then likely an xtea is wrong,
     #[cfg(feature = "dat2")]
     XteaError {
@@ -152,6 +212,34 @@ pub enum CacheErrorKind {
     XteaLoadError(serde_json::Error, PathBuf),
 }
 
+impl CacheErrorKind {
+    /// The name of this kind of error, without any of its contents.
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "sqlite")]
+            Self::SqliteError(_) => "SqliteError",
+            Self::DecodeError(_) => "DecodeError",
+            Self::IoError(..) => "IoError",
+            Self::JsonEncodeError(..) => "JsonEncodeError",
+            Self::CrcError(..) => "CrcError",
+            Self::VersionError(..) => "VersionError",
+            Self::DecompressionError(_) => "DecompressionError",
+            Self::CacheNotFoundError(..) => "CacheNotFoundError",
+            Self::ArchiveNotFoundError(..) => "ArchiveNotFoundError",
+            Self::NamedArchiveNotFoundError(..) => "NamedArchiveNotFoundError",
+            Self::FileMissingError(..) => "FileMissingError",
+            Self::ReadError(_) => "ReadError",
+            Self::SectorError(..) => "SectorError",
+            Self::PlaneError(_) => "PlaneError",
+            Self::ImageError(..) => "ImageError",
+            #[cfg(feature = "dat2")]
+            Self::XteaError { .. } => "XteaError",
+            #[cfg(feature = "dat2")]
+            Self::XteaLoadError(..) => "XteaLoadError",
+        }
+    }
+}
+
 #[cfg(feature = "sqlite")]
 impl From<rusqlite::Error> for CacheError {
     fn from(cause: rusqlite::Error) -> Self {
@@ -228,13 +316,13 @@ impl Display for CacheError {
                     f,
                     "Encountered Error: \x1B[91m{e:?}\x1B[0m \n while looking for file \x1B[93m{path:?}\x1B[0m.\n",
                 )?;
-                match &**input {
-                    CachePath::Given(path) => writeln!(f, "note: looking in this location because the path {path:?} was given as an argument")?,
-                    CachePath::Env(path) => writeln!(
+                match input.origin() {
+                    PathOrigin::Given(path) => writeln!(f, "note: looking in this location because the path {path:?} was given as an argument")?,
+                    PathOrigin::Env(path) => writeln!(
                         f,
                         "note: looking in this location because the path {path:?} was retrieved from an environment variable"
                     )?,
-                    CachePath::Omitted => writeln!(f, "note: looking in the current directory because no path was given")?,
+                    PathOrigin::Omitted => writeln!(f, "note: looking in the current directory because no path was given")?,
                 }
                 let path = (**input).as_ref();
                 let path = path_absolutize::Absolutize::absolutize(path).unwrap_or(std::borrow::Cow::Borrowed(path));
@@ -253,8 +341,14 @@ impl Display for CacheError {
                 write!(f, "Index 5 does not contain mapsquare ({}, {})", archive & 0x7F, archive >> 7)?
             }
             CacheErrorKind::ArchiveNotFoundError(index, archive) => writeln!(f, "Index {index} does not contain archive {archive}")?,
+            CacheErrorKind::NamedArchiveNotFoundError(index, name, hash) => {
+                write!(f, "Index {index} does not contain an archive named {name} (name hash {hash})")?
+            }
             CacheErrorKind::FileMissingError(index, archive, file) => write!(f, "\nIndex {index}, Archive {archive} does not contain file {file}")?,
             CacheErrorKind::IoError(io, path) => write!(f, "encountered {io} while handling path {path:?}")?,
+            CacheErrorKind::SectorError(index, archive, sector) => write!(f, "Index {index}, Archive {archive} has a malformed sector {sector}")?,
+            CacheErrorKind::PlaneError(plane) => write!(f, "Plane {plane} does not exist, planes are in the range 0..4")?,
+            CacheErrorKind::ImageError(cause, path) => write!(f, "Unable to save image to {path:?}: {cause}")?,
             _ => {
                 if let Some(source) = self.source() {
                     write!(f, "Caused by: {source}")?;
@@ -354,7 +448,9 @@ pub mod py_error_impl {
         fn from(err: &CacheError) -> PyErr {
             match err.kind() {
                 CacheErrorKind::CacheNotFoundError(..) => CacheNotFoundError::new_err(err.to_string()),
-                CacheErrorKind::ArchiveNotFoundError(..) => ArchiveNotFoundError::new_err(err.to_string()),
+                CacheErrorKind::ArchiveNotFoundError(..) | CacheErrorKind::NamedArchiveNotFoundError(..) => {
+                    ArchiveNotFoundError::new_err(err.to_string())
+                }
                 CacheErrorKind::FileMissingError(..) => FileMissingError::new_err(err.to_string()),
                 #[cfg(feature = "dat2")]
                 CacheErrorKind::XteaError { .. } => XteaError::new_err(err.to_string()),
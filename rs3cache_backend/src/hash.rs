@@ -7,6 +7,16 @@ pub fn hash_djb2(s: impl AsRef<[u8]>) -> i32 {
     n
 }
 
+/// The 64 bit FNV-1a hash of `s`, which unlike [`DefaultHasher`](std::collections::hash_map::DefaultHasher) is stable across releases.
+pub fn hash_fnv1a(s: impl AsRef<[u8]>) -> u64 {
+    let mut n: u64 = 0xcbf2_9ce4_8422_2325;
+    for x in s.as_ref() {
+        n ^= *x as u64;
+        n = n.wrapping_mul(0x0100_0000_01b3);
+    }
+    n
+}
+
 #[cfg(feature = "dat")]
 pub fn hash_archive(s: &str) -> i32 {
     let mut n: i32 = 0;
@@ -46,3 +56,15 @@ mod legacy {
         assert_eq!(hash, -923525801);
     }
 }
+
+#[cfg(test)]
+mod fnv_tests {
+    use super::*;
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(hash_fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(hash_fnv1a("a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash_fnv1a("foobar"), 0x85944171f73967e8);
+    }
+}
@@ -10,11 +10,13 @@ use std::{
     ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use bytes::{Buf, Bytes};
 use itertools::iproduct;
 use path_macro::path;
+use serde::Serialize;
 
 #[cfg(feature = "dat2")]
 use crate::xtea::Xtea;
@@ -63,6 +65,9 @@ pub struct CacheIndex<S: IndexState> {
     #[cfg(feature = "sqlite")]
     connection: rusqlite::Connection,
 
+    #[cfg(feature = "sqlite")]
+    crc_checks: bool,
+
     #[cfg(any(feature = "dat2", feature = "dat"))]
     file: File,
 
@@ -70,8 +75,19 @@ pub struct CacheIndex<S: IndexState> {
     xteas: Option<HashMap<u32, Xtea>>,
 }
 
+/// Where the cache is found, and how it is opened.
+#[derive(Clone, Debug, Default)]
+pub struct CachePath {
+    origin: PathOrigin,
+    layout: CacheLayout,
+
+    #[cfg(feature = "sqlite")]
+    busy_timeout: Option<Duration>,
+}
+
+/// How the path to the cache was given.
 #[derive(Clone, Debug, Default)]
-pub enum CachePath {
+pub enum PathOrigin {
     #[default]
     Omitted,
     Env(PathBuf),
@@ -80,20 +96,165 @@ pub enum CachePath {
 
 impl AsRef<Path> for CachePath {
     fn as_ref(&self) -> &Path {
-        match self {
-            CachePath::Omitted => Path::new(""),
-            CachePath::Env(p) | CachePath::Given(p) => p,
+        match &self.origin {
+            PathOrigin::Omitted => Path::new(""),
+            PathOrigin::Env(p) | PathOrigin::Given(p) => p,
+        }
+    }
+}
+
+impl From<PathOrigin> for CachePath {
+    fn from(origin: PathOrigin) -> Self {
+        Self {
+            origin,
+            ..Default::default()
         }
     }
 }
 
 impl CachePath {
+    /// A path that was given as an argument.
+    pub fn given(path: impl Into<PathBuf>) -> Self {
+        PathOrigin::Given(path.into()).into()
+    }
+
+    /// A path that was retrieved from an environment variable.
+    pub fn env(path: impl Into<PathBuf>) -> Self {
+        PathOrigin::Env(path.into()).into()
+    }
+
+    /// How the path to the cache was given.
+    pub fn origin(&self) -> &PathOrigin {
+        &self.origin
+    }
+
     pub fn to_path_buf(&self) -> PathBuf {
-        match self {
-            CachePath::Omitted => PathBuf::new(),
-            CachePath::Env(p) | CachePath::Given(p) => p.to_path_buf(),
+        self.as_ref().to_path_buf()
+    }
+
+    /// Sets how long [`CacheIndex::new`] waits for the cache to be unlocked, for example by the game or its launcher,
+    /// before failing with `SQLITE_BUSY`. Sqlite retries with backoff for at most this long.
+    ///
+    /// If this is not set, sqlite's default of five seconds is used.
+    #[cfg(feature = "sqlite")]
+    pub fn with_busy_timeout(self, timeout: Duration) -> Self {
+        Self {
+            busy_timeout: Some(timeout),
+            ..self
         }
     }
+
+    /// The busy timeout set by [`with_busy_timeout`](CachePath::with_busy_timeout).
+    #[cfg(feature = "sqlite")]
+    pub fn busy_timeout(&self) -> Option<Duration> {
+        self.busy_timeout
+    }
+
+    /// Sets where the cache files are found, for caches that are not laid out like the game does.
+    pub fn with_layout(self, layout: CacheLayout) -> Self {
+        Self { layout, ..self }
+    }
+
+    /// The [`CacheLayout`] set by [`with_layout`](CachePath::with_layout), or the default layout.
+    pub fn layout(&self) -> &CacheLayout {
+        &self.layout
+    }
+
+    /// The folder holding the cache files.
+    pub fn folder(&self) -> PathBuf {
+        self.as_ref().join(&self.layout().folder)
+    }
+
+    /// The file holding index `index_id`.
+    pub fn index_file(&self, index_id: u32) -> PathBuf {
+        self.folder().join(self.layout().index_file_name(index_id))
+    }
+
+    /// The file holding the data of all indices.
+    #[cfg(any(feature = "dat2", feature = "dat"))]
+    pub fn data_file(&self) -> PathBuf {
+        self.folder().join(&self.layout().data_file)
+    }
+
+    /// Returns the ids of all indices that have an index file, excluding the reference table 255, in ascending order.
+    ///
+    /// Index files are recognized by the [`CacheLayout`], by default `js5-{index_id}.jcache` for rs3 caches
+    /// and `cache/main_file_cache.idx{index_id}` for the others.
+    ///
+    /// Returns an empty vector if the folder cannot be read.
+    pub fn present_indices(&self) -> Vec<u32> {
+        let layout = self.layout();
+        let mut indices: Vec<u32> = fs::read_dir(self.folder())
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| layout.index_id(entry.ok()?.file_name().to_str()?))
+            .filter(|index_id| *index_id != 255)
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+/// Where the files of a cache are found, relative to its [`CachePath`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheLayout {
+    /// The folder holding the cache files, relative to the [`CachePath`].
+    pub folder: PathBuf,
+
+    /// The file name of each index, with `{index_id}` in place of the index id.
+    pub index_file: String,
+
+    /// The file name of the file holding the data of all indices.
+    #[cfg(any(feature = "dat2", feature = "dat"))]
+    pub data_file: String,
+}
+
+impl Default for CacheLayout {
+    #[cfg(feature = "sqlite")]
+    fn default() -> Self {
+        Self {
+            folder: PathBuf::new(),
+            index_file: "js5-{index_id}.jcache".to_string(),
+        }
+    }
+
+    #[cfg(feature = "dat2")]
+    fn default() -> Self {
+        Self {
+            folder: PathBuf::from("cache"),
+            index_file: "main_file_cache.idx{index_id}".to_string(),
+            data_file: "main_file_cache.dat2".to_string(),
+        }
+    }
+
+    #[cfg(feature = "dat")]
+    fn default() -> Self {
+        Self {
+            folder: PathBuf::from("cache"),
+            index_file: "main_file_cache.idx{index_id}".to_string(),
+            data_file: "main_file_cache.dat".to_string(),
+        }
+    }
+}
+
+impl CacheLayout {
+    /// The file name of index `index_id`.
+    pub fn index_file_name(&self, index_id: u32) -> String {
+        self.index_file.replace("{index_id}", &index_id.to_string())
+    }
+
+    /// The index id of the index file named `file_name`, ignoring case.
+    ///
+    /// Returns `None` if `file_name` is not an index file.
+    pub fn index_id(&self, file_name: &str) -> Option<u32> {
+        let (prefix, suffix) = self.index_file.split_once("{index_id}")?;
+        let file_name = file_name.to_lowercase();
+        file_name
+            .strip_prefix(&prefix.to_lowercase())?
+            .strip_suffix(&suffix.to_lowercase())?
+            .parse()
+            .ok()
+    }
 }
 
 // methods valid in any state
@@ -114,6 +275,13 @@ where
         &(self.metadatas)
     }
 
+    /// Returns the name and id of every named archive in `self`, given a list of names that archives may have.
+    ///
+    /// See [`IndexMetadata::named`].
+    pub fn named_archives<'a>(&self, candidates: impl IntoIterator<Item = &'a str>) -> Vec<(String, u32)> {
+        self.metadatas().named(candidates)
+    }
+
     /// Get an [`Archive`] from `self`.
     ///
     /// # Errors
@@ -177,6 +345,8 @@ impl CacheIndex<Initial> {
             path,
             #[cfg(feature = "sqlite")]
             connection,
+            #[cfg(feature = "sqlite")]
+            crc_checks,
             #[cfg(any(feature = "dat2", feature = "dat"))]
             file,
             index_id,
@@ -190,6 +360,8 @@ impl CacheIndex<Initial> {
             path,
             #[cfg(feature = "sqlite")]
             connection,
+            #[cfg(feature = "sqlite")]
+            crc_checks,
             #[cfg(any(feature = "dat2", feature = "dat"))]
             file,
             index_id,
@@ -201,6 +373,166 @@ impl CacheIndex<Initial> {
     }
 }
 
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+impl CacheIndex<Initial> {
+    /// Chains `self` with `fallback`, see [`ChainedCacheIndex`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fallback` has a different index id.
+    pub fn with_fallback(self, fallback: CacheIndex<Initial>) -> ChainedCacheIndex {
+        ChainedCacheIndex {
+            metadatas: self.metadatas.clone(),
+            indices: vec![self],
+        }
+        .with_fallback(fallback)
+    }
+}
+
+/// A sequence of [`CacheIndex`]es with the same index id, such as an update cache on top of the cache it updates.
+///
+/// Archives are read from the first index that has them, so that archives of earlier indices replace those of later ones.
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+pub struct ChainedCacheIndex {
+    indices: Vec<CacheIndex<Initial>>,
+    metadatas: IndexMetadata,
+}
+
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+impl ChainedCacheIndex {
+    /// Appends `fallback`, to be read from if none of the current indices have an archive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fallback` has a different index id.
+    pub fn with_fallback(mut self, fallback: CacheIndex<Initial>) -> Self {
+        assert_eq!(self.index_id(), fallback.index_id(), "Attempted to chain indices with different ids");
+        self.metadatas.union(fallback.metadatas());
+        self.indices.push(fallback);
+        self
+    }
+
+    /// The index id of `self`.
+    #[inline(always)]
+    pub fn index_id(&self) -> u32 {
+        self.indices[0].index_id()
+    }
+
+    /// Returns the union of the [`IndexMetadata`] of all chained indices.
+    ///
+    /// If several indices have the same archive, the [`Metadata`] of the first one is returned.
+    #[inline(always)]
+    pub fn metadatas(&self) -> &IndexMetadata {
+        &self.metadatas
+    }
+
+    /// Get an [`Archive`] from the first index that has it.
+    ///
+    /// # Errors
+    ///
+    /// Raises [`ArchiveNotFoundError`](crate::error::CacheErrorKind::ArchiveNotFoundError) if `archive_id` is in none of the indices.
+    pub fn archive(&self, archive_id: u32) -> CacheResult<Archive> {
+        self.indices
+            .iter()
+            .find(|index| index.metadatas().get(&archive_id).is_some())
+            .ok_or_else(|| CacheError::archive_missing(self.index_id(), archive_id))?
+            .archive(archive_id)
+    }
+
+    /// Get the decompressed contents of the archive of `metadata` from the first index that has it,
+    /// see [`CacheIndex::get_file`].
+    ///
+    /// # Errors
+    ///
+    /// Raises [`ArchiveNotFoundError`](crate::error::CacheErrorKind::ArchiveNotFoundError) if the archive is in none of the indices.
+    pub fn get_file(&self, metadata: &Metadata) -> CacheResult<Bytes> {
+        self.indices
+            .iter()
+            .find(|index| index.metadatas().get(&metadata.archive_id()).is_some())
+            .ok_or_else(|| CacheError::archive_missing(self.index_id(), metadata.archive_id()))?
+            .get_file(metadata)
+    }
+}
+
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+impl IntoIterator for ChainedCacheIndex {
+    type Item = CacheResult<Archive>;
+
+    type IntoIter = ChainedIntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let feed = self.metadatas().keys().copied().collect::<Vec<u32>>().into_iter();
+
+        ChainedIntoIter { index: self, feed }
+    }
+}
+
+/// Iterator over all [`Archive`]s of a [`ChainedCacheIndex`], each read from the first index that has it.
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ChainedIntoIter {
+    index: ChainedCacheIndex,
+    feed: std::vec::IntoIter<u32>,
+}
+
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+impl Iterator for ChainedIntoIter {
+    type Item = CacheResult<Archive>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.feed.next().map(|archive_id| self.index.archive(archive_id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.feed.size_hint()
+    }
+}
+
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+impl ExactSizeIterator for ChainedIntoIter {}
+
+/// A summary of the contents of a cache, see [`cache_fingerprint`].
+///
+/// Digests are formatted as hexadecimal strings.
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CacheFingerprint {
+    /// The digest of all indices.
+    pub root: String,
+    /// The digest of the [`IndexMetadata`] of each index.
+    pub indices: BTreeMap<u32, String>,
+}
+
+/// Computes a [`CacheFingerprint`] of the cache at `path`, from the id, crc and version of every archive.
+///
+/// Two caches have the same root digest if their indices have the same archives, so this can be used
+/// to tell whether a cache is a specific build without looking at the archives themselves.
+///
+/// # Errors
+///
+/// Raises [`CacheNotFoundError`](crate::error::CacheErrorKind::CacheNotFoundError) if any of the indices cannot be opened.
+#[cfg(any(feature = "sqlite", feature = "dat2"))]
+pub fn cache_fingerprint(path: Arc<CachePath>) -> CacheResult<CacheFingerprint> {
+    let digests = path
+        .present_indices()
+        .into_iter()
+        .map(|index_id| Ok((index_id, CacheIndex::new(index_id, path.clone())?.metadatas().digest())))
+        .collect::<CacheResult<BTreeMap<u32, u64>>>()?;
+
+    let root: Vec<u8> = digests
+        .iter()
+        .flat_map(|(index_id, digest)| index_id.to_be_bytes().into_iter().chain(digest.to_be_bytes()))
+        .collect();
+
+    Ok(CacheFingerprint {
+        root: format!("{:016x}", crate::hash::hash_fnv1a(root)),
+        indices: digests
+            .into_iter()
+            .map(|(index_id, digest)| (index_id, format!("{digest:016x}")))
+            .collect(),
+    })
+}
+
 impl IntoIterator for CacheIndex<Initial> {
     type Item = CacheResult<Archive>;
 
@@ -223,6 +555,8 @@ impl IntoIterator for CacheIndex<Truncated> {
             path,
             #[cfg(feature = "sqlite")]
             connection,
+            #[cfg(feature = "sqlite")]
+            crc_checks,
             #[cfg(any(feature = "dat2", feature = "dat"))]
             file,
             index_id,
@@ -236,6 +570,8 @@ impl IntoIterator for CacheIndex<Truncated> {
             path,
             #[cfg(feature = "sqlite")]
             connection,
+            #[cfg(feature = "sqlite")]
+            crc_checks,
             #[cfg(any(feature = "dat2", feature = "dat"))]
             file,
             index_id,
@@ -279,3 +615,94 @@ impl Iterator for IntoIter {
 }
 
 impl ExactSizeIterator for IntoIter {}
+
+#[cfg(test)]
+mod layout_tests {
+    use super::*;
+
+    #[test]
+    fn index_file_names() {
+        let layout = CacheLayout {
+            folder: PathBuf::new(),
+            index_file: "Idx{index_id}.bin".to_string(),
+            #[cfg(any(feature = "dat2", feature = "dat"))]
+            data_file: "data.bin".to_string(),
+        };
+        assert_eq!(layout.index_file_name(12), "Idx12.bin");
+        assert_eq!(layout.index_id("idx12.BIN"), Some(12));
+        assert_eq!(layout.index_id("idx.bin"), None);
+        assert_eq!(layout.index_id("data.bin"), None);
+    }
+
+    #[test]
+    fn default_layout() {
+        let layout = CacheLayout::default();
+        assert_eq!(layout.index_id(&layout.index_file_name(255)), Some(255));
+    }
+
+    #[test]
+    fn layouts_are_per_path() {
+        let layout = CacheLayout {
+            folder: PathBuf::from("files"),
+            index_file: "{index_id}.idx".to_string(),
+            ..Default::default()
+        };
+        let custom = CachePath::given("a").with_layout(layout);
+        let default = CachePath::given("b");
+
+        assert_eq!(custom.index_file(2), Path::new("a/files/2.idx"));
+        assert_eq!(
+            default.index_file(2),
+            Path::new("b").join(&default.layout().folder).join(default.layout().index_file_name(2))
+        );
+    }
+
+    #[test]
+    fn present_indices() {
+        let root = std::env::temp_dir().join("rs3cache_present_indices");
+        let path = CachePath::given(&root);
+        fs::create_dir_all(path.folder()).unwrap();
+        for index_id in [12, 2, 255] {
+            fs::write(path.index_file(index_id), []).unwrap();
+        }
+        fs::write(path.folder().join("unrelated.txt"), []).unwrap();
+
+        assert_eq!(path.present_indices(), vec![2, 12]);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
+
+#[cfg(all(test, any(feature = "sqlite", feature = "dat2"), feature = "mockdata"))]
+mod chain_tests {
+    use super::*;
+
+    fn path() -> Arc<CachePath> {
+        #[cfg(feature = "sqlite")]
+        let path = "../test_data/rs3_cache";
+        #[cfg(feature = "dat2")]
+        let path = "../test_data/osrs_cache";
+        Arc::new(CachePath::given(path))
+    }
+
+    #[test]
+    fn fallback() -> CacheResult<()> {
+        let complete = CacheIndex::new(2, path())?;
+        let archive_id = *complete.metadatas().keys().next().unwrap();
+        let metadata = complete.metadatas().get(&archive_id).unwrap().clone();
+
+        let mut first = CacheIndex::new(2, path())?;
+        first.metadatas.remove(archive_id);
+        assert!(first.archive(archive_id).is_err());
+
+        let chained = first.with_fallback(CacheIndex::new(2, path())?);
+        assert_eq!(chained.get_file(&metadata)?, complete.get_file(&metadata)?);
+        assert_eq!(chained.archive(archive_id)?.archive_id(), archive_id);
+
+        let archives = chained
+            .into_iter()
+            .map(|archive| archive.map(|archive| archive.archive_id()))
+            .collect::<CacheResult<Vec<_>>>()?;
+        assert_eq!(archives, complete.metadatas().keys().copied().collect::<Vec<_>>());
+        Ok(())
+    }
+}
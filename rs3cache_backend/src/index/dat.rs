@@ -11,7 +11,6 @@ use std::{
 
 use bytes::{Buf, Bytes};
 use itertools::iproduct;
-use path_macro::path;
 
 use crate::{
     arc::Archive,
@@ -27,7 +26,7 @@ where
     S: IndexState,
 {
     fn get_entry(a: u32, b: u32, path: &Arc<CachePath>) -> CacheResult<(u32, u32)> {
-        let file = path!(**path / "cache" / format!("main_file_cache.idx{a}"));
+        let file = path.index_file(a);
         let entry_data = match fs::read(&file) {
             Ok(f) => f,
             Err(e) => return Err(CacheError::cache_not_found(e, file, path.clone())),
@@ -43,13 +42,23 @@ where
 
         let (length, mut sector) = Self::get_entry(a, b, &self.path)?;
 
+        // A corrupt entry must not make us allocate more than the file could possibly contain.
+        let file_length = self.file.metadata().map(|metadata| metadata.len()).unwrap_or(u64::MAX);
+        if length as u64 > file_length {
+            return Err(ReadError::eof().into());
+        }
+
         let mut read_count = 0;
         let mut part = 0;
         let mut data = Vec::with_capacity(length as _);
+        let mut visited = BTreeSet::new();
 
         while sector != 0 {
-            buffer.seek(SeekFrom::Start((sector * 520) as _)).map_err(|_| ReadError::eof())?;
-            let (_header_size, current_archive, block_size) = if b >= 0xFFFF {
+            if !visited.insert(sector) {
+                return Err(CacheError::sector(a, b, sector));
+            }
+            buffer.seek(SeekFrom::Start(sector as u64 * 520)).map_err(|_| ReadError::eof())?;
+            let (_header_size, current_archive, block_size) = if b > 0xFFFF {
                 let mut buf = [0; 4];
                 buffer.read_exact(&mut buf).map_err(|_| ReadError::eof())?;
                 (10, i32::from_be_bytes(buf), 510.min(length - read_count))
@@ -75,8 +84,9 @@ where
                 u8::from_be_bytes(buf)
             };
 
-            assert_eq!(b, current_archive as u32);
-            assert_eq!(part, current_part as u32);
+            if b != current_archive as u32 || part != current_part as u32 {
+                return Err(CacheError::sector(a, b, sector));
+            }
 
             part += 1;
             read_count += block_size;
@@ -99,6 +109,11 @@ where
         Ok(decoder::decompress(data)?)
     }
 
+    /// Like [`get_file`](CacheIndex::get_file), but returns the archive's container as it is stored, without decompressing it.
+    pub fn get_file_raw(&self, metadata: &Metadata) -> CacheResult<Bytes> {
+        Ok(Bytes::from(self.read_index(metadata.index_id(), metadata.archive_id())?))
+    }
+
     pub fn archive_by_name(&self, name: String) -> CacheResult<Bytes> {
         let hash = crate::hash::hash_archive(&name);
         for (_, m) in self.metadatas.iter() {
@@ -158,7 +173,7 @@ impl CacheIndex<Initial> {
     ///
     /// Raises [`CacheNotFoundError`](CacheError::CacheNotFoundError) if the cache database cannot be found.
     pub fn new(index_id: u32, path: Arc<CachePath>) -> CacheResult<CacheIndex<Initial>> {
-        let file = path!(&*path / "cache/main_file_cache.dat");
+        let file = path.data_file();
 
         let file = match File::open(&file) {
             Ok(f) => f,
@@ -182,3 +197,42 @@ pub struct MapsquareMeta {
     pub locfile: u16,
     pub f2p: bool,
 }
+
+#[cfg(test)]
+mod dat_tests {
+    use super::*;
+    use crate::error::CacheErrorKind;
+
+    /// Writes a cache with a single entry for archive 0 of index 1, whose one sector links to `next_sector`.
+    fn write_cache(name: &str, length: u32, next_sector: u32) -> Arc<CachePath> {
+        let root = env::temp_dir().join(name);
+        fs::create_dir_all(root.join("cache")).unwrap();
+
+        let mut entry = length.to_be_bytes()[1..].to_vec();
+        entry.extend(&1_u32.to_be_bytes()[1..]);
+        fs::write(root.join("cache/main_file_cache.idx1"), entry).unwrap();
+
+        let mut sector = vec![0; 520];
+        sector.extend([0, 0, 0, 0]);
+        sector.extend(&next_sector.to_be_bytes()[1..]);
+        sector.push(1);
+        sector.extend([0; 512]);
+        fs::write(root.join("cache/main_file_cache.dat"), sector).unwrap();
+
+        Arc::new(CachePath::given(root))
+    }
+
+    #[test]
+    fn sector_cycle() {
+        let index = CacheIndex::new(1, write_cache("rs3cache_sector_cycle", 600, 1)).unwrap();
+        let err = index.read_index(1, 0).unwrap_err();
+        assert!(matches!(err.kind(), CacheErrorKind::SectorError(1, 0, 1)));
+    }
+
+    #[test]
+    fn oversized_length() {
+        let index = CacheIndex::new(1, write_cache("rs3cache_oversized_length", 0xFF_FFFF, 0)).unwrap();
+        let err = index.read_index(1, 0).unwrap_err();
+        assert!(matches!(err.kind(), CacheErrorKind::ReadError(_)));
+    }
+}
@@ -27,25 +27,39 @@ where
     S: IndexState,
 {
     fn get_entry(a: u32, b: u32, path: &Arc<CachePath>) -> CacheResult<(u32, u32)> {
-        let file = path!(**path / "cache" / format!("main_file_cache.idx{a}"));
+        let file = path.index_file(a);
         let entry_data = fs::read(&file).map_err(|e| CacheError::cache_not_found(e, file, path.clone()))?;
         let mut buf = Cursor::new(entry_data);
         buf.seek(SeekFrom::Start((b * 6) as _)).unwrap();
         Ok((buf.try_get_uint(3)? as u32, buf.try_get_uint(3)? as u32))
     }
 
+    /// Reads archive `b` of index `a` from `main_file_cache.dat2`, following its chain of 520 byte sectors.
+    ///
+    /// Archives with an id of more than `0xFFFF` use the extended sector header with a 4 byte archive id,
+    /// leaving 510 rather than 512 bytes of data per sector. This is the layout that openrs2 exports as well.
     fn read_index(&self, a: u32, b: u32) -> CacheResult<Vec<u8>> {
         let mut buffer = BufReader::new(&self.file);
 
         let (length, mut sector) = Self::get_entry(a, b, &self.path)?;
 
+        // A corrupt entry must not make us allocate more than the file could possibly contain.
+        let file_length = self.file.metadata().map(|metadata| metadata.len()).unwrap_or(u64::MAX);
+        if length as u64 > file_length {
+            return Err(ReadError::eof().into());
+        }
+
         let mut read_count = 0;
         let mut part = 0;
         let mut data = Vec::with_capacity(length as _);
+        let mut visited = BTreeSet::new();
 
         while sector != 0 {
-            buffer.seek(SeekFrom::Start((sector * 520) as _)).map_err(|_| ReadError::eof())?;
-            let (_header_size, current_archive, block_size) = if b >= 0xFFFF {
+            if !visited.insert(sector) {
+                return Err(CacheError::sector(a, b, sector));
+            }
+            buffer.seek(SeekFrom::Start(sector as u64 * 520)).map_err(|_| ReadError::eof())?;
+            let (_header_size, current_archive, block_size) = if b > 0xFFFF {
                 let mut buf = [0; 4];
                 buffer.read_exact(&mut buf).map_err(|_| ReadError::eof())?;
                 (10, i32::from_be_bytes(buf), 510.min(length - read_count))
@@ -71,9 +85,9 @@ where
                 u8::from_be_bytes(buf)
             };
 
-            assert_eq!(a, current_index as u32);
-            assert_eq!(b, current_archive as u32);
-            assert_eq!(part, current_part as u32);
+            if a != current_index as u32 || b != current_archive as u32 || part != current_part as u32 {
+                return Err(CacheError::sector(a, b, sector));
+            }
 
             part += 1;
             read_count += block_size;
@@ -92,6 +106,11 @@ where
         Ok(decoder::decompress(data, None)?)
     }
 
+    /// Like [`get_file`](CacheIndex::get_file), but returns the archive's container as it is stored, without decompressing it.
+    pub fn get_file_raw(&self, metadata: &Metadata) -> CacheResult<Bytes> {
+        Ok(Bytes::from(self.read_index(metadata.index_id(), metadata.archive_id())?))
+    }
+
     pub fn xteas(&self) -> &Option<HashMap<u32, Xtea>> {
         &self.xteas
     }
@@ -124,7 +143,7 @@ impl CacheIndex<Initial> {
     ///
     /// Raises [`CacheNotFoundError`](CacheError::CacheNotFoundError) if the cache database cannot be found.
     pub fn new(index_id: u32, path: Arc<CachePath>) -> CacheResult<CacheIndex<Initial>> {
-        let file = path!(path.as_ref() / "cache" / "main_file_cache.dat2");
+        let file = path.data_file();
 
         let file = match File::open(&file) {
             Ok(f) => f,
@@ -171,3 +190,68 @@ impl CacheIndex<Initial> {
         Ok(s)
     }
 }
+
+#[cfg(test)]
+mod dat2_tests {
+    use super::*;
+
+    /// Writes a cache with a single entry for `archive` of index 1, whose one sector links to `next_sector`.
+    ///
+    /// The sector holds the bytes `0, 1, 2, ...` as its data.
+    fn write_cache(name: &str, archive: u32, length: u32, next_sector: u32) -> CacheIndex<Initial> {
+        let root = env::temp_dir().join(name);
+        fs::create_dir_all(root.join("cache")).unwrap();
+
+        let mut entry = vec![0; archive as usize * 6];
+        entry.extend(&length.to_be_bytes()[1..]);
+        entry.extend(&1_u32.to_be_bytes()[1..]);
+        fs::write(root.join("cache/main_file_cache.idx1"), entry).unwrap();
+
+        let mut sector = vec![0; 520];
+        if archive > 0xFFFF {
+            sector.extend(archive.to_be_bytes());
+        } else {
+            sector.extend((archive as u16).to_be_bytes());
+        }
+        sector.extend([0, 0]);
+        sector.extend(&next_sector.to_be_bytes()[1..]);
+        sector.push(1);
+        sector.extend((0..).take(1040 - sector.len()).map(|i: u32| i as u8));
+        fs::write(root.join("cache/main_file_cache.dat2"), sector).unwrap();
+
+        let path = Arc::new(CachePath::given(root));
+        // Not through `CacheIndex::new`, as that would need a reference table as well.
+        CacheIndex {
+            file: File::open(path.data_file()).unwrap(),
+            path,
+            index_id: 1,
+            metadatas: IndexMetadata::empty(),
+            xteas: None,
+            state: Initial {},
+        }
+    }
+
+    #[test]
+    fn sector_cycle() {
+        let index = write_cache("rs3cache_dat2_sector_cycle", 0, 600, 1);
+        let err = index.read_index(1, 0).unwrap_err();
+        assert!(matches!(err.kind(), CacheErrorKind::SectorError(1, 0, 1)), "{err}");
+    }
+
+    #[test]
+    fn oversized_length() {
+        let index = write_cache("rs3cache_dat2_oversized_length", 0, 0xFF_FFFF, 0);
+        let err = index.read_index(1, 0).unwrap_err();
+        assert!(matches!(err.kind(), CacheErrorKind::ReadError(_)), "{err}");
+    }
+
+    /// Archive `0xFFFF` still has the short sector header, `0x10000` is the first with the extended one.
+    #[test]
+    fn extended_header_boundary() {
+        for (archive, name) in [(0xFFFF, "rs3cache_dat2_archive_ffff"), (0x10000, "rs3cache_dat2_archive_10000")] {
+            let index = write_cache(name, archive, 500, 0);
+            let data = index.read_index(1, archive).unwrap();
+            assert_eq!(data, (0..500).map(|i: u32| i as u8).collect::<Vec<_>>(), "archive {archive:#x}");
+        }
+    }
+}
@@ -11,7 +11,7 @@ use std::{
 
 use bytes::{Buf, Bytes};
 use itertools::iproduct;
-use path_macro::path;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::{
     arc::Archive,
@@ -22,6 +22,17 @@ use crate::{
     meta::{IndexMetadata, Metadata},
 };
 
+/// The difference between the crc stored alongside an archive in the cache and the crc in its [`Metadata`].
+///
+/// The stored crc is one more than the crc in the [`Metadata`], except for the sprites (8) and `MODELSRT7` (47) indices,
+/// where it is two more. Why those differ is unknown.
+fn crc_offset(index_id: u32) -> i64 {
+    match index_id {
+        8 | 47 => 2,
+        _ => 1,
+    }
+}
+
 impl<S> CacheIndex<S>
 where
     S: IndexState,
@@ -35,8 +46,35 @@ where
         Ok(decoder::decompress(data)?)
     }
 
+    /// Sets whether [`get_file`](CacheIndex::get_file) verifies the crc and version of archives against the metadata.
+    ///
+    /// This is enabled by default. Disabling it allows reading hand-edited or partially repacked caches,
+    /// as well as the indices that tend to never complete (see [`assert_coherence`](CacheIndex::assert_coherence)).
+    pub fn with_crc_checks(mut self, crc_checks: bool) -> Self {
+        self.crc_checks = crc_checks;
+        self
+    }
+
     /// Executes a sql command to retrieve an archive from the cache.
+    ///
+    /// # Errors
+    ///
+    /// Raises [`CrcError`](CacheError::CrcError) or [`VersionError`](CacheError::VersionError)
+    /// if the archive does not match its metadata, unless disabled with [`with_crc_checks`](CacheIndex::with_crc_checks).
     pub fn get_file(&self, metadata: &Metadata) -> CacheResult<Bytes> {
+        Ok(decoder::decompress(self.get_data(metadata)?)?)
+    }
+
+    /// Like [`get_file`](CacheIndex::get_file), but returns the archive's container as it is stored, without decompressing it.
+    ///
+    /// # Errors
+    ///
+    /// Raises the same errors as [`get_file`](CacheIndex::get_file), except for those of decompression.
+    pub fn get_file_raw(&self, metadata: &Metadata) -> CacheResult<Bytes> {
+        Ok(Bytes::from(self.get_data(metadata)?))
+    }
+
+    fn get_data(&self, metadata: &Metadata) -> CacheResult<Vec<u8>> {
         let mut stmt = self.connection.prepare("SELECT DATA, CRC, VERSION FROM cache WHERE KEY=?")?;
         let mut rows = stmt.query([metadata.archive_id()])?;
         let row = rows
@@ -46,15 +84,12 @@ where
         let crc = row.get(1).unwrap();
         let version = row.get(2).unwrap();
 
-        // wut
-        let crc_offset = match self.index_id() {
-            8 => 2_i64,
-            47 => 2_i64,
-            _ => 1_i64,
-        };
+        let crc_offset = crc_offset(self.index_id());
 
         if crc == 0 && version == 0 {
             Err(CacheError::archive_missing(metadata.index_id(), metadata.archive_id()))
+        } else if !self.crc_checks {
+            Ok(data)
         } else if metadata.crc() as i64 + crc_offset != crc {
             Err(CacheError::crc(
                 metadata.index_id(),
@@ -70,53 +105,58 @@ where
                 version,
             ))
         } else {
-            Ok(decoder::decompress(data)?)
+            Ok(data)
         }
     }
 
     /// Assert whether the cache held by `self` is in a coherent state.
     ///
+    /// The crc and version of every archive are read with a single query, rather than one per archive.
+    ///
     /// # Errors
     ///
-    /// May raise [`CrcError`](CacheError::CrcError), [`VersionError`](CacheError::VersionError) or [`ArchiveNotFoundError`](CacheError::ArchiveNotFoundError)
-    /// if the cache is not in a logical state.
+    /// Returns every [`CrcError`](CacheError::CrcError), [`VersionError`](CacheError::VersionError) and [`ArchiveNotFoundError`](CacheError::ArchiveNotFoundError)
+    /// if the cache is not in a logical state, or the error that prevented reading it.
     ///
     /// # Notes
     /// Indices `VORBIS`, `AUDIOSTREAMS`, `TEXTURES_PNG_MIPPED` and `TEXTURES_ETC` tend to never complete.
     /// For these, simply ignore [`ArchiveNotFoundError`](CacheError::ArchiveNotFoundError).
-    pub fn assert_coherence(&self) -> CacheResult<()> {
-        for (archive_id, metadata) in self.metadatas().iter() {
-            let mut stmt = self.connection.prepare("SELECT CRC, VERSION FROM cache WHERE KEY=?")?;
-            let mut rows = stmt.query([archive_id])?;
-            let row = rows.next()?.unwrap();
-            let crc = row.get(0).unwrap();
-            let version = row.get(1).unwrap();
-
-            // wut
-            let crc_offset = match self.index_id() {
-                8 => 2_i64,
-                47 => 2_i64,
-                _ => 1_i64,
-            };
-            if crc == 0 && version == 0 {
-                return Err(CacheError::archive_missing(metadata.index_id(), metadata.archive_id()));
-            } else if metadata.crc() as i64 + crc_offset != crc {
-                return Err(CacheError::crc(
+    pub fn assert_coherence(&self) -> Result<(), Vec<CacheError>> {
+        let stored: CacheResult<HashMap<u32, (i64, i64)>> = try {
+            let mut stmt = self.connection.prepare("SELECT KEY, CRC, VERSION FROM cache")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+            rows.collect::<Result<_, rusqlite::Error>>()?
+        };
+        let stored = stored.map_err(|e| vec![e])?;
+
+        let crc_offset = crc_offset(self.index_id());
+
+        let errors: Vec<CacheError> = self
+            .metadatas()
+            .iter()
+            .filter_map(|(archive_id, metadata)| match stored.get(archive_id) {
+                None | Some((0, 0)) => Some(CacheError::archive_missing(metadata.index_id(), metadata.archive_id())),
+                Some(&(crc, _)) if metadata.crc() as i64 + crc_offset != crc => Some(CacheError::crc(
                     metadata.index_id(),
                     metadata.archive_id(),
                     metadata.crc() as i64 + crc_offset,
                     crc,
-                ));
-            } else if metadata.version() as i64 != version {
-                return Err(CacheError::version(
+                )),
+                Some(&(_, version)) if metadata.version() as i64 != version => Some(CacheError::version(
                     metadata.index_id(),
                     metadata.archive_id(),
                     metadata.version() as i64,
                     version,
-                ));
-            }
+                )),
+                Some(_) => None,
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
         }
-        Ok(())
     }
 }
 
@@ -127,12 +167,15 @@ impl CacheIndex<Initial> {
     ///
     /// Raises [`CacheNotFoundError`](CacheError::CacheNotFoundError) if the cache database cannot be found.
     pub fn new(index_id: u32, path: Arc<CachePath>) -> CacheResult<CacheIndex<Initial>> {
-        let file = path!(path.as_ref() / format!("js5-{index_id}.jcache"));
+        let file = path.index_file(index_id);
 
         // check if database exists (without creating blank sqlite databases)
         match fs::metadata(&file) {
             Ok(_) => {
                 let connection = rusqlite::Connection::open(file)?;
+                if let Some(timeout) = path.busy_timeout() {
+                    connection.busy_timeout(timeout)?;
+                }
                 let raw_metadata: Bytes = Self::get_raw_metadata(&connection)?;
                 let metadatas = IndexMetadata::deserialize(index_id, raw_metadata)?;
 
@@ -140,6 +183,7 @@ impl CacheIndex<Initial> {
                     index_id,
                     metadatas,
                     connection,
+                    crc_checks: true,
                     path,
                     state: Initial {},
                 })
@@ -159,13 +203,95 @@ impl CacheIndex<Initial> {
 /// Panics if compiled with feature `mockdata`.
 #[cfg(not(feature = "mockdata"))]
 pub fn assert_coherence(folder: Arc<CachePath>) -> CacheResult<()> {
-    for index_id in 0..70 {
-        if fs::metadata(path!(&*folder / format!("js5-{index_id}.jcache"))).is_ok() {
-            match CacheIndex::new(index_id, folder.clone())?.assert_coherence() {
-                Ok(_) => println!("Index {index_id} is coherent!"),
-                Err(e) => println!("Index {index_id} is not coherent: {e} and possibly others."),
+    // Each index is its own database, so every thread gets its own connection.
+    let reports = folder
+        .present_indices()
+        .into_par_iter()
+        .map(|index_id| Ok((index_id, CacheIndex::new(index_id, folder.clone())?.assert_coherence())))
+        .collect::<CacheResult<Vec<_>>>()?;
+
+    for (index_id, report) in reports {
+        match report {
+            Ok(_) => log::info!("Index {index_id} is coherent!"),
+            Err(errors) => {
+                log::warn!("Index {index_id} is not coherent, with {} errors:", errors.len());
+                for e in errors {
+                    log::warn!("Index {index_id}: {e}");
+                }
             }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod sqlite_tests {
+    use super::*;
+    use crate::error::CacheErrorKind;
+
+    fn path() -> Arc<CachePath> {
+        #[cfg(feature = "mockdata")]
+        let path = CachePath::given("../test_data/rs3_cache");
+        #[cfg(not(feature = "mockdata"))]
+        let path = CachePath::env(std::env::var_os("RS3_CACHE_INPUT_FOLDER").unwrap_or_default());
+        Arc::new(path)
+    }
+
+    #[test]
+    fn crc_offsets() {
+        assert_eq!(crc_offset(2), 1);
+        assert_eq!(crc_offset(8), 2);
+        assert_eq!(crc_offset(47), 2);
+    }
+
+    /// Compares the stored crc of every archive in the index that is present to its metadata.
+    fn assert_crcs(index_id: u32) -> CacheResult<()> {
+        let index = CacheIndex::new(index_id, path())?;
+        let errors = index.assert_coherence().err().unwrap_or_default();
+        let (missing, mismatched): (Vec<_>, Vec<_>) = errors.iter().partition(|e| matches!(e.kind(), CacheErrorKind::ArchiveNotFoundError(..)));
+
+        assert!(mismatched.is_empty(), "{mismatched:?}");
+        assert!(
+            missing.len() < index.metadatas().keys().len(),
+            "index {index_id} has no archives to check"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn config_crcs() -> CacheResult<()> {
+        assert_crcs(2)
+    }
+
+    // These indices store their crcs with a different offset than the others.
+    #[test]
+    fn sprites_crcs() -> CacheResult<()> {
+        assert_crcs(8)
+    }
+
+    #[test]
+    fn crc_checks() -> CacheResult<()> {
+        let index = CacheIndex::new(2, path())?;
+        let mut metadata = index
+            .metadatas()
+            .iter()
+            .map(|(_, metadata)| metadata)
+            .find(|metadata| index.get_file(metadata).is_ok())
+            .expect("index 2 has no readable archives")
+            .clone();
+        metadata.crc = metadata.crc.wrapping_add(1);
+
+        let err = index.get_file(&metadata).unwrap_err();
+        assert!(matches!(err.kind(), CacheErrorKind::CrcError(..)), "{err}");
+
+        let index = index.with_crc_checks(false);
+        index.get_file(&metadata)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "mockdata"))]
+    #[test]
+    fn modelsrt7_crcs() -> CacheResult<()> {
+        assert_crcs(47)
+    }
+}
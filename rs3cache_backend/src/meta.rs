@@ -3,7 +3,7 @@
 use std::{
     collections::{
         btree_map::{IntoIter, Iter, Keys},
-        BTreeMap,
+        BTreeMap, HashMap,
     },
     iter,
     ops::Add,
@@ -58,6 +58,8 @@ pub struct Metadata {
     pub child_count: u32,
     #[cfg_attr(feature = "pyo3", pyo3(get))]
     pub child_indices: Vec<u32>,
+    #[cfg_attr(feature = "pyo3", pyo3(get))]
+    pub child_names: Option<Vec<i32>>,
 }
 
 #[cfg(feature = "pyo3")]
@@ -175,6 +177,12 @@ impl Metadata {
     pub fn child_indices(&self) -> &[u32] {
         &self.child_indices
     }
+
+    /// The hashed names of the files in the [`Archive`](crate::arc::Archive), in the same order as [`child_indices`](Metadata::child_indices), if present.
+    #[inline(always)]
+    pub fn child_names(&self) -> Option<&[i32]> {
+        self.child_names.as_deref()
+    }
 }
 
 /// Contains the [`Metadata`] for every [`Archive`](crate::arc::Archive) in the index.
@@ -288,6 +296,21 @@ impl IndexMetadata {
             })
             .collect::<Vec<Vec<u32>>>();
 
+        let child_names = if named {
+            child_counts
+                .iter()
+                .map(|count| try {
+                    Some(
+                        iter::repeat_with(|| buffer.try_get_i32())
+                            .take(*count as usize)
+                            .collect::<Result<Vec<i32>, ReadError>>()?,
+                    )
+                })
+                .collect::<Result<Vec<Option<Vec<i32>>>, ReadError>>()?
+        } else {
+            vec![None; entry_count]
+        };
+
         let metadatas = izip!(
             archive_ids,
             names,
@@ -298,10 +321,11 @@ impl IndexMetadata {
             sizes,
             versions,
             child_counts,
-            child_indices
+            child_indices,
+            child_names
         )
         .map(
-            |(archive_id, name, crc, unknown, digest, compressed_size, size, version, child_count, child_indices)| {
+            |(archive_id, name, crc, unknown, digest, compressed_size, size, version, child_count, child_indices, child_names)| {
                 (
                     archive_id,
                     Metadata {
@@ -316,6 +340,7 @@ impl IndexMetadata {
                         digest,
                         child_count,
                         child_indices,
+                        child_names,
                     },
                 )
             },
@@ -343,6 +368,53 @@ impl IndexMetadata {
     pub fn metadatas(&self) -> &BTreeMap<u32, Metadata> {
         &self.metadatas
     }
+
+    /// Returns the name and id of every named archive in `self`, in order of archive id.
+    ///
+    /// Names are found by hashing each of `candidates` and comparing it to the [`name`](Metadata::name) of each archive.
+    /// Archives whose name is not among `candidates` are named by their hash instead.
+    pub fn named<'a>(&self, candidates: impl IntoIterator<Item = &'a str>) -> Vec<(String, u32)> {
+        #[cfg(not(feature = "dat"))]
+        let hash = |name: &str| crate::hash::hash_djb2(name);
+        #[cfg(feature = "dat")]
+        let hash = crate::hash::hash_archive;
+
+        let names: HashMap<i32, &str> = candidates.into_iter().map(|name| (hash(name), name)).collect();
+        self.iter()
+            .filter_map(|(archive_id, metadata)| {
+                let name_hash = metadata.name()?;
+                let name = names.get(&name_hash).map_or_else(|| name_hash.to_string(), |name| name.to_string());
+                Some((name, *archive_id))
+            })
+            .collect()
+    }
+
+    /// A digest of the id, crc and version of every archive in `self`.
+    ///
+    /// Archives are hashed in order of their id, so equal metadata always has the same digest.
+    pub fn digest(&self) -> u64 {
+        let mut bytes = Vec::with_capacity(self.metadatas.len() * 12);
+        for (archive_id, metadata) in self.iter() {
+            bytes.extend(archive_id.to_be_bytes());
+            bytes.extend(metadata.crc().to_be_bytes());
+            bytes.extend(metadata.version().to_be_bytes());
+        }
+        crate::hash::hash_fnv1a(bytes)
+    }
+
+    /// Removes the [`Metadata`] of `archive_id`, as if the index did not have that archive.
+    #[cfg(test)]
+    pub(crate) fn remove(&mut self, archive_id: u32) -> Option<Metadata> {
+        self.metadatas.remove(&archive_id)
+    }
+
+    /// Adds the [`Metadata`] of every archive in `other` that is not in `self`.
+    #[cfg(any(feature = "sqlite", feature = "dat2"))]
+    pub(crate) fn union(&mut self, other: &IndexMetadata) {
+        for (archive_id, metadata) in other.iter() {
+            self.metadatas.entry(*archive_id).or_insert_with(|| metadata.clone());
+        }
+    }
 }
 
 impl IntoIterator for IndexMetadata {
@@ -354,3 +426,32 @@ impl IntoIterator for IndexMetadata {
         self.metadatas.into_iter()
     }
 }
+
+#[cfg(test)]
+mod meta_tests {
+    use super::*;
+
+    #[test]
+    fn named() {
+        #[cfg(not(feature = "dat"))]
+        let hash = |name: &str| crate::hash::hash_djb2(name);
+        #[cfg(feature = "dat")]
+        let hash = crate::hash::hash_archive;
+
+        let metadata = |archive_id, name| Metadata {
+            archive_id,
+            name,
+            ..Default::default()
+        };
+        let index = IndexMetadata {
+            metadatas: BTreeMap::from([
+                (0, metadata(0, Some(hash("title")))),
+                (1, metadata(1, Some(hash("unknown")))),
+                (2, metadata(2, None)),
+            ]),
+        };
+
+        let named = index.named(["title", "config"]);
+        assert_eq!(named, vec![("title".to_string(), 0), (hash("unknown").to_string(), 1)]);
+    }
+}
@@ -1,11 +1,31 @@
-use std::{ffi::OsStr, fmt, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    ops::{BitOr, RangeInclusive},
+    path::PathBuf,
+    sync::Arc,
+};
 
+use bytes::Bytes;
 use clap::{ArgEnum, Parser};
-use rs3cache_backend::{error::CacheResult, index::CachePath};
+use rs3cache_backend::{
+    error::{CacheError, CacheResult},
+    index::{CacheIndex, CachePath},
+};
+use serde::Serialize;
 
 use crate::definitions;
+#[cfg(all(not(target_arch = "wasm32"), any(feature = "rs3", feature = "osrs")))]
+use crate::renderers::palette;
 #[cfg(not(target_arch = "wasm32"))]
-use crate::renderers::map;
+use crate::renderers::{
+    encode::ImageFormat,
+    hillshade,
+    map::{self, PlaneMode},
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(ArgEnum, Clone, Debug)]
@@ -13,6 +33,10 @@ use crate::renderers::map;
 pub enum Render {
     All,
     Map,
+    #[cfg(any(feature = "rs3", feature = "osrs"))]
+    Palette,
+    MapsceneAtlas,
+    Hillshade,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -21,6 +45,10 @@ impl Render {
         match self {
             Render::All => map::render(config)?,
             Render::Map => map::render(config)?,
+            #[cfg(any(feature = "rs3", feature = "osrs"))]
+            Render::Palette => palette::export_palette(config)?,
+            Render::MapsceneAtlas => map::mapscenes::export_atlas(config)?,
+            Render::Hillshade => hillshade::hillshade(config)?,
         };
 
         Ok(())
@@ -39,7 +67,9 @@ pub enum Dump {
     Sprites,
     Locations,
     LocationsEach,
+    LocationsCombined,
     TilesEach,
+    MapsquareManifest,
     LocationConfigs,
     LocationConfigsEach,
     NpcConfig,
@@ -50,6 +80,8 @@ pub enum Dump {
     VarbitConfigs,
     Structs,
     Enums,
+    #[cfg(feature = "rs3")]
+    DbTables,
     #[cfg(any(feature = "rs3", feature = "osrs"))]
     Underlays,
     #[cfg(any(feature = "rs3", feature = "osrs"))]
@@ -69,6 +101,8 @@ impl Dump {
             Dump::TilesEach => definitions::mapsquares::export_tiles_by_square,
             Dump::Locations => definitions::mapsquares::export_locations_by_id,
             Dump::LocationsEach => definitions::mapsquares::export_locations_by_square,
+            Dump::LocationsCombined => definitions::mapsquares::export_locations_combined,
+            Dump::MapsquareManifest => definitions::mapsquares::export_mapsquare_manifest,
             Dump::LocationConfigs => definitions::location_configs::export,
             Dump::LocationConfigsEach => definitions::location_configs::export_each,
             Dump::NpcConfig => definitions::npc_configs::export,
@@ -84,6 +118,8 @@ impl Dump {
             Dump::VarbitConfigs => definitions::varbit_configs::export,
             Dump::Structs => definitions::structs::export,
             Dump::Enums => definitions::enums::export,
+            #[cfg(feature = "rs3")]
+            Dump::DbTables => definitions::dbtables::export,
             #[cfg(any(feature = "rs3", feature = "osrs"))]
             Dump::Underlays => definitions::underlays::export,
             #[cfg(any(feature = "rs3", feature = "osrs"))]
@@ -104,6 +140,8 @@ impl Dump {
             Dump::TilesEach => "tiles_by_square",
             Dump::Locations => "locations_by_id",
             Dump::LocationsEach => "locations_by_square",
+            Dump::LocationsCombined => "locations_combined",
+            Dump::MapsquareManifest => "mapsquare_manifest",
             Dump::LocationConfigs => "location_configs",
             Dump::LocationConfigsEach => "location_configs_each",
             Dump::NpcConfig => "npc_configs",
@@ -114,6 +152,8 @@ impl Dump {
             Dump::VarbitConfigs => "varbit_configs",
             Dump::Structs => "structs",
             Dump::Enums => "enums",
+            #[cfg(feature = "rs3")]
+            Dump::DbTables => "dbtables",
             #[cfg(any(feature = "rs3", feature = "osrs"))]
             Dump::Underlays => "underlays",
             #[cfg(any(feature = "rs3", feature = "osrs"))]
@@ -136,6 +176,8 @@ impl Dump {
             Dump::VarbitConfigs,
             Dump::Structs,
             Dump::Enums,
+            #[cfg(feature = "rs3")]
+            Dump::DbTables,
             #[cfg(any(feature = "rs3", feature = "osrs"))]
             Dump::Underlays,
             #[cfg(any(feature = "rs3", feature = "osrs"))]
@@ -152,8 +194,362 @@ impl fmt::Display for Dump {
     }
 }
 
+/// A selection of [`Dump`]s, as taken by [`dump_all`].
+///
+/// Dumps can be combined with `|`, as in `Dump::Underlays | Dump::Overlays`.
+/// [`Dump::All`] and [`Dump::Configs`] select all the dumps they stand for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DumpSet {
+    dumps: BTreeSet<Dump>,
+}
+
+impl DumpSet {
+    /// Adds `dump` to `self`.
+    pub fn insert(&mut self, dump: Dump) {
+        match dump {
+            Dump::All => self.dumps.extend(
+                Dump::value_variants()
+                    .iter()
+                    .filter(|dump| !matches!(dump, Dump::All | Dump::Configs))
+                    .cloned(),
+            ),
+            Dump::Configs => self.dumps.extend(Dump::configs().iter().cloned()),
+            dump => {
+                self.dumps.insert(dump);
+            }
+        }
+    }
+
+    /// Whether `dump` is selected.
+    pub fn contains(&self, dump: &Dump) -> bool {
+        self.dumps.contains(dump)
+    }
+
+    /// The number of selected dumps.
+    pub fn len(&self) -> usize {
+        self.dumps.len()
+    }
+
+    /// Whether nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.dumps.is_empty()
+    }
+
+    /// Iterates over the selected dumps, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &Dump> {
+        self.dumps.iter()
+    }
+}
+
+impl From<Dump> for DumpSet {
+    fn from(dump: Dump) -> Self {
+        let mut set = DumpSet::default();
+        set.insert(dump);
+        set
+    }
+}
+
+impl FromIterator<Dump> for DumpSet {
+    fn from_iter<I: IntoIterator<Item = Dump>>(iter: I) -> Self {
+        let mut set = DumpSet::default();
+        iter.into_iter().for_each(|dump| set.insert(dump));
+        set
+    }
+}
+
+impl IntoIterator for DumpSet {
+    type Item = Dump;
+    type IntoIter = std::collections::btree_set::IntoIter<Dump>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.dumps.into_iter()
+    }
+}
+
+impl BitOr<Dump> for Dump {
+    type Output = DumpSet;
+
+    fn bitor(self, rhs: Dump) -> DumpSet {
+        DumpSet::from(self) | rhs
+    }
+}
+
+impl BitOr<Dump> for DumpSet {
+    type Output = DumpSet;
+
+    fn bitor(mut self, rhs: Dump) -> DumpSet {
+        self.insert(rhs);
+        self
+    }
+}
+
+/// The outcome of every [`Dump`] run by [`dump_all`].
+#[derive(Debug, Default)]
+pub struct DumpSummary {
+    results: BTreeMap<Dump, CacheResult<()>>,
+}
+
+impl DumpSummary {
+    /// The outcome of `dump`, if it was selected.
+    pub fn get(&self, dump: &Dump) -> Option<&CacheResult<()>> {
+        self.results.get(dump)
+    }
+
+    /// The dumps that succeeded, in ascending order.
+    pub fn succeeded(&self) -> impl Iterator<Item = &Dump> {
+        self.results.iter().filter(|(_, result)| result.is_ok()).map(|(dump, _)| dump)
+    }
+
+    /// The dumps that failed with their error, in ascending order.
+    pub fn failed(&self) -> impl Iterator<Item = (&Dump, &CacheError)> {
+        self.results.iter().filter_map(|(dump, result)| Some((dump, result.as_ref().err()?)))
+    }
+
+    /// The number of selected dumps that were configs (see [`Dump::configs`]) and of those that were not,
+    /// each as `(succeeded, total)`.
+    pub fn counts(&self) -> [(&'static str, usize, usize); 2] {
+        let (configs, others): (Vec<_>, Vec<_>) = self.results.iter().partition(|(dump, _)| Dump::configs().contains(dump));
+        [("configs", configs), ("other", others)].map(|(category, results)| {
+            let succeeded = results.iter().filter(|(_, result)| result.is_ok()).count();
+            (category, succeeded, results.len())
+        })
+    }
+
+    /// Returns the error of the first dump that failed, if any.
+    pub fn into_result(self) -> CacheResult<()> {
+        self.results.into_values().collect()
+    }
+}
+
+impl fmt::Display for DumpSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (category, succeeded, total) in self.counts() {
+            if total != 0 {
+                writeln!(f, "    {category}: dumped {succeeded} of {total}")?;
+            }
+        }
+        for (dump, _) in self.failed() {
+            writeln!(f, "    failed dumping {dump}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every exporter in `selection` in parallel, showing which are running on a progress bar.
+///
+/// All exporters are run, even if some fail.
+/// The returned [`DumpSummary`] holds the outcome of each of them; [`DumpSummary::into_result`] turns it into an error.
+///
+/// # Errors
+///
+/// Only returns an error if the output folder cannot be created.
+pub fn dump_all(config: &Config, selection: DumpSet) -> CacheResult<DumpSummary> {
+    use std::sync::Mutex;
+
+    use console::style;
+    use indicatif::{ProgressBar, ProgressStyle};
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.to_path_buf()))?;
+
+    let progress = ProgressBar::new(selection.len() as u64).with_style(
+        ProgressStyle::with_template(&format!(
+            "   {} [{{bar:30}}] {{pos}}/{{len}}: {{wide_msg}}",
+            style("Dumping").cyan().bright()
+        ))
+        .unwrap()
+        .progress_chars("=> "),
+    );
+
+    let running = Mutex::new(Vec::new());
+
+    let results = selection
+        .into_iter()
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|dump| {
+            let name = dump.as_str();
+            {
+                let mut running = running.lock().unwrap();
+                running.push(name);
+                progress.set_message(running.join(", "));
+            }
+
+            let result = dump.call()(config);
+            progress.inc(1);
+            {
+                let mut running = running.lock().unwrap();
+                running.retain(|&n| n != name);
+                progress.set_message(running.join(", "));
+
+                match &result {
+                    Ok(()) => progress.println(format!("    {} {name}", style("Dumped").green().bright())),
+                    Err(e) => {
+                        log::warn!("Failed dumping {dump}: {e}");
+                        progress.println(format!("    {} failed dumping {dump}", style("Error").red()))
+                    }
+                }
+            }
+
+            (dump, result)
+        })
+        .collect();
+    progress.finish_and_clear();
+
+    Ok(DumpSummary { results })
+}
+
+/// The format in which exporters write their output.
+#[derive(ArgEnum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[clap(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// Compact json.
+    Json,
+    /// Indented json.
+    #[default]
+    JsonPretty,
+    /// Bincode, see <https://docs.rs/bincode>.
+    #[cfg(feature = "binary")]
+    Bincode,
+    /// MessagePack, see <https://msgpack.org>.
+    #[cfg(feature = "binary")]
+    MessagePack,
+}
+
+impl OutputFormat {
+    /// The file extension of this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Json | OutputFormat::JsonPretty => "json",
+            #[cfg(feature = "binary")]
+            OutputFormat::Bincode => "bin",
+            #[cfg(feature = "binary")]
+            OutputFormat::MessagePack => "msgpack",
+        }
+    }
+}
+
+/// The compression applied to exported files.
+#[derive(ArgEnum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[clap(rename_all = "snake_case")]
+pub enum Compression {
+    /// Files are written as-is.
+    #[default]
+    None,
+    /// Gzip, appending `.gz` to the file name.
+    #[cfg(feature = "compress")]
+    Gzip,
+    /// Zstandard, appending `.zst` to the file name.
+    #[cfg(feature = "compress")]
+    Zstd,
+}
+
+impl Compression {
+    /// The extension appended by this compression, if any.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            #[cfg(feature = "compress")]
+            Compression::Gzip => Some("gz"),
+            #[cfg(feature = "compress")]
+            Compression::Zstd => Some("zst"),
+        }
+    }
+}
+
+/// Returns the path that [`write_serialized`] writes to, given `path`.
+///
+/// The extension of `path` is replaced by the [extension](OutputFormat::extension) of the output format,
+/// followed by the [extension](Compression::extension) of the compression, if any.
+pub fn output_path(path: PathBuf, config: &Config) -> PathBuf {
+    match config.compress_output.extension() {
+        Some(compression) => path.with_extension(format!("{}.{compression}", config.format.extension())),
+        None => path.with_extension(config.format.extension()),
+    }
+}
+
+/// Serializes `value` in the [format](OutputFormat) and [compression](Compression) given by `config`,
+/// and writes it to the [`output_path`] of `path`.
+///
+/// The output is written to a temporary file first, so an interrupted write never leaves a partial file at `path`.
+pub fn write_serialized<T: Serialize + ?Sized>(path: PathBuf, value: &T, config: &Config) -> CacheResult<()> {
+    let path = output_path(path, config);
+    let mut partial = path.clone().into_os_string();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+
+    let file = File::create(&partial).map_err(|e| CacheError::io(e, partial.clone()))?;
+    let writer = BufWriter::new(file);
+
+    let result: io::Result<BufWriter<File>> = match config.compress_output {
+        Compression::None => serialize_into(writer, value, config.format),
+        #[cfg(feature = "compress")]
+        Compression::Gzip => {
+            let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            serialize_into(encoder, value, config.format).and_then(|encoder| encoder.finish())
+        }
+        #[cfg(feature = "compress")]
+        Compression::Zstd => zstd::Encoder::new(writer, 0)
+            .and_then(|encoder| serialize_into(encoder, value, config.format))
+            .and_then(|encoder| encoder.finish()),
+    };
+
+    result
+        .and_then(|mut writer| writer.flush())
+        .map_err(|e| CacheError::io(e, partial.clone()))?;
+    fs::rename(&partial, &path).map_err(|e| CacheError::io(e, path))
+}
+
+fn serialize_into<W: Write, T: Serialize + ?Sized>(mut writer: W, value: &T, format: OutputFormat) -> io::Result<W> {
+    match format {
+        OutputFormat::Json => serde_json::to_writer(&mut writer, value).map_err(io::Error::from),
+        OutputFormat::JsonPretty => serde_json::to_writer_pretty(&mut writer, value).map_err(io::Error::from),
+        #[cfg(feature = "binary")]
+        OutputFormat::Bincode => bincode::serialize_into(&mut writer, value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        #[cfg(feature = "binary")]
+        OutputFormat::MessagePack => rmp_serde::encode::write_named(&mut writer, value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+    }?;
+    Ok(writer)
+}
+
+/// Reads file `file_id` of archive `archive_id` in index `index_id`, decompressed.
+pub fn dump_file(config: &Config, index_id: u32, archive_id: u32, file_id: u32) -> CacheResult<Bytes> {
+    CacheIndex::new(index_id, config.input.clone())?.archive(archive_id)?.file(&file_id)
+}
+
+/// Reads archive `archive_id` in index `index_id` as it is stored, without decompressing it.
+pub fn dump_archive_raw(config: &Config, index_id: u32, archive_id: u32) -> CacheResult<Bytes> {
+    let index = CacheIndex::new(index_id, config.input.clone())?;
+    let metadata = index
+        .metadatas()
+        .get(&archive_id)
+        .ok_or_else(|| CacheError::archive_missing(index_id, archive_id))?;
+    index.get_file_raw(metadata)
+}
+
+/// Formats `bytes` as lines of sixteen bytes, each with its offset and a printable representation.
+pub fn hexdump(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for (n, line) in bytes.chunks(16).enumerate() {
+        let hex = line.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+        let text = line
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect::<String>();
+        out.push_str(&format!("{:08x}  {hex:<47}  |{text}|\n", n * 16));
+    }
+    out
+}
+
 fn path_helper(input: &OsStr) -> Arc<CachePath> {
-    Arc::new(CachePath::Given(input.into()))
+    Arc::new(CachePath::given(input))
+}
+
+fn plane_helper(plane: &str) -> Result<(), String> {
+    match plane.parse::<usize>() {
+        Ok(plane) if plane < 4 => Ok(()),
+        _ => Err(format!("{plane} is not a plane, planes are in the range 0..4")),
+    }
 }
 
 const INPUT: &str = if cfg!(feature = "rs3") {
@@ -176,7 +572,7 @@ const OUTPUT: &str = if cfg!(feature = "rs3") {
     unimplemented!()
 };
 
-#[derive(Debug, Default, Parser)]
+#[derive(Debug, Parser)]
 #[clap(author, about = "Tools and api for reading and interpreting the RuneScape game cache")]
 pub struct Config {
     /// The path where to look for the current cache.
@@ -187,6 +583,10 @@ pub struct Config {
     #[clap(long, env = OUTPUT, default_value = "")]
     pub output: PathBuf,
 
+    /// The format in which to export data.
+    #[clap(arg_enum, long, default_value = "json_pretty")]
+    pub format: OutputFormat,
+
     /// This exports them as small tiles, formatted as `<layer>/<mapid>/<zoom>/<plane>_<x>_<y>.png`,
     /// suitable for use with interactive map libraries such as <https://leafletjs.com/>,
     /// as seen on <https://mejrs.github.io/>
@@ -194,21 +594,160 @@ pub struct Config {
     #[clap(arg_enum, long, multiple_values = true)]
     pub render: Vec<Render>,
 
+    /// The compression of exported files.
+    #[clap(arg_enum, long, default_value = "none")]
+    pub compress_output: Compression,
+
+    /// The range, in tiles, at which ground colours are blended. Larger values are smoother, but slower.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(long)]
+    pub interp: Option<u8>,
+
+    /// How the planes of rendered tiles are combined.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(arg_enum, long, default_value = "stacked")]
+    pub plane_mode: PlaneMode,
+
+    /// The image format of rendered tiles.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(arg_enum, long, default_value = "png")]
+    pub image_format: ImageFormat,
+
+    /// The quality of lossy image formats, in the range `0..=100`.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(long, default_value = "80")]
+    pub image_quality: u8,
+
+    /// The direction light comes from in `--render hillshade`, in degrees clockwise from north. Defaults to 315.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(long)]
+    pub hillshade_azimuth: Option<f32>,
+
+    /// The angle of the light above the horizon in `--render hillshade`, in degrees. Defaults to 45.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(long)]
+    pub hillshade_altitude: Option<f32>,
+
+    /// The factor by which heights are multiplied in `--render hillshade`. Defaults to 1.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[clap(long)]
+    pub hillshade_exaggeration: Option<f32>,
+
     /// Dumps the given archives.
     #[clap(arg_enum, long, multiple_values = true)]
     pub dump: Vec<Dump>,
 
+    /// Prints a single file as hex, given as `<index> <archive> <file>`.
+    #[clap(long, number_of_values = 3, value_names = &["INDEX", "ARCHIVE", "FILE"])]
+    pub dump_file: Option<Vec<u32>>,
+
+    /// Dumps the archive of the file given by `--dump-file` as it is stored, without decompressing it.
+    #[clap(long, requires = "dump-file")]
+    pub raw: bool,
+
+    /// Writes the bytes given by `--dump-file` to the output folder, rather than printing them as hex.
+    #[clap(long, requires = "dump-file")]
+    pub write_file: bool,
+
+    /// Prints the tile and locations at a world coordinate, given as `<plane> <x> <y>`.
+    #[clap(long, number_of_values = 3, value_names = &["PLANE", "X", "Y"])]
+    pub inspect_tile: Option<Vec<u16>>,
+
+    /// Prints a digest of the archives in every index, to tell whether two caches are the same.
+    #[cfg(any(feature = "rs3", feature = "osrs"))]
+    #[clap(long)]
+    pub fingerprint: bool,
+
+    /// Location ids to leave out of `--dump locations`.
+    #[clap(long, multiple_values = true)]
+    pub skip_location_ids: Vec<u32>,
+
+    /// Skips squares whose output already exists and is newer than the cache, to resume an interrupted `--dump locations_each` or `--dump tiles_each`.
+    #[clap(long)]
+    pub resume: bool,
+
+    /// Also saves the archive version of every square to `versions.json` when exporting squares, to tell which cache they came from.
+    #[clap(long)]
+    pub include_version: bool,
+
+    /// The planes `--dump tiles_each` keeps, given as `<first> <last>`. Keeps all planes if omitted.
+    #[clap(long, number_of_values = 2, value_names = &["FIRST", "LAST"], validator = plane_helper)]
+    pub planes: Option<Vec<usize>>,
+
+    /// How long to wait, in milliseconds, for the cache to be unlocked if another program is using it.
+    #[cfg(feature = "rs3")]
+    #[clap(long)]
+    pub busy_timeout: Option<u64>,
+
+    /// The number of threads to export with. Uses all cores if omitted.
+    #[clap(long)]
+    pub threads: Option<usize>,
+
     /// Checks whether the cache is in a consistent state.
     /// Indices 14, 40, 54, 55 are not necessarily complete.
     #[clap(long)]
     pub assert_coherence: bool,
 }
 
+/// The same defaults as when no arguments are given, except that `input` and `output` are not read from the environment.
+/// See [`Config::env`] for that.
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            input: Default::default(),
+            output: Default::default(),
+            format: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            render: Default::default(),
+            compress_output: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            interp: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            plane_mode: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            image_format: Default::default(),
+            #[cfg(not(target_arch = "wasm32"))]
+            image_quality: 80,
+            #[cfg(not(target_arch = "wasm32"))]
+            hillshade_azimuth: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            hillshade_altitude: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            hillshade_exaggeration: None,
+            dump: Default::default(),
+            dump_file: None,
+            raw: false,
+            write_file: false,
+            inspect_tile: None,
+            #[cfg(any(feature = "rs3", feature = "osrs"))]
+            fingerprint: false,
+            skip_location_ids: Default::default(),
+            resume: false,
+            include_version: false,
+            planes: None,
+            #[cfg(feature = "rs3")]
+            busy_timeout: None,
+            threads: None,
+            assert_coherence: false,
+        }
+    }
+}
+
 impl Config {
+    /// The planes given by `--planes`, in either order, or `0..=3`.
+    ///
+    /// Planes past 3 are rejected when the arguments are parsed.
+    pub fn planes(&self) -> RangeInclusive<usize> {
+        match self.planes.as_deref() {
+            Some(&[first, last]) => first.min(last)..=first.max(last).min(3),
+            _ => 0..=3,
+        }
+    }
+
     #[cfg(not(feature = "mockdata"))]
     pub fn env() -> Self {
         Self {
-            input: Arc::new(CachePath::Env(std::env::var_os(INPUT).unwrap_or_default().into())),
+            input: Arc::new(CachePath::env(std::env::var_os(INPUT).unwrap_or_default())),
             output: std::env::var_os(OUTPUT).unwrap_or_default().into(),
             ..Default::default()
         }
@@ -217,7 +756,7 @@ impl Config {
     #[cfg(all(feature = "osrs", feature = "mockdata"))]
     pub fn env() -> Self {
         Self {
-            input: Arc::new(CachePath::Given(PathBuf::from("test_data/osrs_cache"))),
+            input: Arc::new(CachePath::given(PathBuf::from("test_data/osrs_cache"))),
             ..Default::default()
         }
     }
@@ -225,7 +764,7 @@ impl Config {
     #[cfg(all(feature = "rs3", feature = "mockdata"))]
     pub fn env() -> Self {
         Self {
-            input: Arc::new(CachePath::Given(PathBuf::from("test_data/rs3_cache"))),
+            input: Arc::new(CachePath::given(PathBuf::from("test_data/rs3_cache"))),
             ..Default::default()
         }
     }
@@ -233,8 +772,80 @@ impl Config {
     #[cfg(all(feature = "legacy", feature = "mockdata"))]
     pub fn env() -> Self {
         Self {
-            input: Arc::new(CachePath::Given(PathBuf::from("test_data/2005_cache"))),
+            input: Arc::new(CachePath::given(PathBuf::from("test_data/2005_cache"))),
             ..Default::default()
         }
     }
 }
+
+#[cfg(test)]
+mod dump_set_tests {
+    use super::*;
+
+    #[test]
+    fn expands() {
+        let set = Dump::Configs | Dump::Locations;
+        assert!(set.contains(&Dump::Locations));
+        assert!(set.contains(&Dump::Enums));
+        assert!(!set.contains(&Dump::Configs));
+        assert_eq!(set.len(), Dump::configs().len() + 1);
+
+        let all = DumpSet::from(Dump::All);
+        assert!(!all.contains(&Dump::All));
+        assert_eq!(all.len(), Dump::value_variants().len() - 2);
+    }
+
+    #[test]
+    fn summary_counts() {
+        let summary = DumpSummary {
+            results: BTreeMap::from([
+                (Dump::Enums, Ok(())),
+                (Dump::Structs, Err(CacheError::plane(4))),
+                (Dump::Locations, Ok(())),
+            ]),
+        };
+        assert_eq!(summary.counts(), [("configs", 1, 2), ("other", 1, 1)]);
+        assert_eq!(summary.failed().map(|(dump, _)| dump).collect::<Vec<_>>(), [&Dump::Structs]);
+        assert!(summary.to_string().contains("failed dumping structs"));
+        assert!(summary.into_result().is_err());
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    #[test]
+    fn planes() {
+        let config = Config::default();
+        assert_eq!(config.planes(), 0..=3);
+
+        let config = Config {
+            planes: Some(vec![0, 0]),
+            ..Default::default()
+        };
+        assert_eq!(config.planes(), 0..=0);
+
+        let config = Config::try_parse_from(["rs3cache", "--planes", "3", "1"]).unwrap();
+        assert_eq!(config.planes(), 1..=3);
+    }
+
+    #[test]
+    fn invalid_planes() {
+        assert!(Config::try_parse_from(["rs3cache", "--planes", "2", "4"]).is_err());
+        assert!(Config::try_parse_from(["rs3cache", "--planes", "-1", "2"]).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn default_matches_arguments() {
+        let parsed = Config::try_parse_from(["rs3cache"]).unwrap();
+        let default = Config::default();
+        assert_eq!(default.image_quality, 80);
+        assert_eq!(default.image_quality, parsed.image_quality);
+        assert_eq!(default.format, parsed.format);
+        assert_eq!(default.image_format, parsed.image_format);
+        assert_eq!(default.plane_mode, parsed.plane_mode);
+        assert_eq!(default.compress_output, parsed.compress_output);
+    }
+}
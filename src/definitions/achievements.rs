@@ -1,10 +1,5 @@
 //! Describes the properties of Achievements.
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-    iter,
-};
+use std::{collections::BTreeMap, fs, iter};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -466,9 +461,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     achievement_configs.sort_unstable_by_key(|loc| loc.id);
 
     let path = path!(config.output / "achievements.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&achievement_configs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &achievement_configs, config)?;
 
     Ok(())
 }
@@ -9,6 +9,7 @@ use serde::Serialize;
 use crate::{
     cache::{buf::BufExtra, error::CacheResult, index::CacheIndex},
     definitions::indextype::{ConfigType, IndexType},
+    structures::paramtable::Param,
 };
 
 #[allow(missing_docs)]
@@ -20,7 +21,8 @@ pub struct DbRow {
     pub id: u32,
     pub unknown_1: Option<bool>,
     pub content_type: Option<u8>,
-    pub data: Option<Vec<Vec<Value>>>,
+    /// The values of each column, as a list of fields that each contain one value per column type.
+    pub data: Option<BTreeMap<u8, Vec<Vec<Param>>>>,
 }
 
 impl DbRow {
@@ -62,35 +64,18 @@ impl DbRow {
                     }
                     1 => obj.unknown_1 = Some(true),
                     3 => {
-                        let size = buffer.try_get_u8()?;
-                        let objects = vec![Value::Null; size as usize];
+                        let _column_count = buffer.try_get_u8()?;
+                        let mut data = BTreeMap::new();
                         loop {
                             match buffer.try_get_u8()? {
                                 255 => break,
-                                index => {
-                                    let amount = buffer.try_get_u8()? as usize;
-                                    let types: Vec<_> = std::iter::repeat_with(|| buffer.get_smarts()).take(amount).collect();
-                                    let count = buffer.get_smarts() as usize;
-
-                                    let subobjects = vec![Value::Null; count * amount];
-                                    for c in 0..count {
-                                        for (pos, ty) in types.iter().enumerate() {
-                                            match ty {
-                                                35 => {
-                                                    buffer.try_get_array::<8>()?;
-                                                }
-                                                36 => {
-                                                    buffer.try_get_string()?;
-                                                }
-                                                _ => {
-                                                    buffer.try_get_i32()?;
-                                                }
-                                            }
-                                        }
-                                    }
+                                column => {
+                                    let types = deserialize_types(&mut buffer)?;
+                                    data.insert(column, deserialize_fields(&types, &mut buffer)?);
                                 }
                             }
                         }
+                        obj.data = Some(data);
                     }
                     4 => obj.content_type = Some(buffer.try_get_u8()?),
                     missing => Err(ReadError::opcode_not_implemented(missing))?,
@@ -108,11 +93,28 @@ impl DbRow {
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub enum Value {
-    Integer(i32),
-    Text(String),
-    Null,
+/// Reads a value of the given column type.
+fn deserialize_value(r#type: u32, buffer: &mut Bytes) -> Result<Param, ReadError> {
+    let value = match r#type {
+        35 => Param::Long(i64::from_be_bytes(buffer.try_get_array()?)),
+        36 => Param::String(buffer.try_get_string()?),
+        _ => Param::Integer(buffer.try_get_i32()?),
+    };
+    Ok(value)
+}
+
+/// Reads the types of a column, as used by both [`DbRow`]s and [`DbTable`](crate::definitions::dbtables::DbTable)s.
+pub(crate) fn deserialize_types(buffer: &mut Bytes) -> Result<Vec<u32>, ReadError> {
+    let amount = buffer.try_get_u8()? as usize;
+    std::iter::repeat_with(|| buffer.try_get_smarts()).take(amount).collect()
+}
+
+/// Reads a count of fields, followed by that many fields that each hold one value per type in `types`.
+pub(crate) fn deserialize_fields(types: &[u32], buffer: &mut Bytes) -> Result<Vec<Vec<Param>>, ReadError> {
+    let count = buffer.try_get_smarts()? as usize;
+    std::iter::repeat_with(|| types.iter().map(|ty| deserialize_value(*ty, buffer)).collect())
+        .take(count)
+        .collect()
 }
 
 use std::fmt::{self, Display, Formatter};
@@ -133,6 +135,7 @@ mod tests {
         let config = Config::env();
 
         let dbrows = DbRow::dump_all(&config)?;
+        assert!(dbrows.values().any(|row| row.data.is_some()));
 
         Ok(())
     }
@@ -0,0 +1,144 @@
+//! Describes the layout of database tables, and the rows they contain.
+
+use std::{collections::BTreeMap, fs};
+
+use bytes::{Buf, Bytes};
+use path_macro::path;
+use rs3cache_backend::{buf::ReadError, error::CacheError};
+use serde::Serialize;
+
+pub use crate::definitions::dbrows::DbRow;
+use crate::{
+    cache::{buf::BufExtra, error::CacheResult, index::CacheIndex},
+    definitions::{
+        dbrows,
+        indextype::{ConfigType, IndexType},
+    },
+    structures::paramtable::Param,
+};
+
+/// Describes the columns of a database table.
+#[cfg_attr(feature = "pyo3", pyo3::pyclass)]
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DbTable {
+    /// Its id.
+    pub id: u32,
+    /// The columns of this table, by column id.
+    pub columns: Option<BTreeMap<u8, Column>>,
+}
+
+/// A column of a [`DbTable`].
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct Column {
+    /// The type of each value in a field of this column.
+    pub types: Vec<u32>,
+    /// The fields a [`DbRow`] has if it does not set this column.
+    pub default: Option<Vec<Vec<Param>>>,
+}
+
+impl DbTable {
+    /// Returns a mapping of all [`DbTable`] configurations.
+    pub fn dump_all(config: &crate::cli::Config) -> CacheResult<BTreeMap<u32, DbTable>> {
+        let files = CacheIndex::new(IndexType::CONFIG, config.input.clone())?
+            .archive(ConfigType::DBTABLE)?
+            .take_files()
+            .into_iter();
+
+        let dbtables = files
+            .map(|(file_id, file)| {
+                DbTable::deserialize(file_id, file)
+                    .map(|item| (file_id, item))
+                    .map_err(|e| e.add_context_id(file_id))
+            })
+            .collect::<Result<BTreeMap<u32, DbTable>, ReadError>>()?;
+        Ok(dbtables)
+    }
+
+    fn deserialize(id: u32, mut buffer: Bytes) -> Result<Self, ReadError> {
+        let mut table = Self { id, ..Default::default() };
+
+        loop {
+            match buffer.try_get_u8()? {
+                0 => {
+                    if buffer.has_remaining() {
+                        return Err(ReadError::not_exhausted());
+                    } else {
+                        break Ok(table);
+                    }
+                }
+                1 => {
+                    let _column_count = buffer.try_get_u8()?;
+                    let mut columns = BTreeMap::new();
+                    loop {
+                        match buffer.try_get_u8()? {
+                            255 => break,
+                            setting => {
+                                let types = dbrows::deserialize_types(&mut buffer)?;
+                                let default = if setting & 0x80 != 0 {
+                                    Some(dbrows::deserialize_fields(&types, &mut buffer)?)
+                                } else {
+                                    None
+                                };
+                                columns.insert(setting & 0x7F, Column { types, default });
+                            }
+                        }
+                    }
+                    table.columns = Some(columns);
+                }
+                missing => return Err(ReadError::opcode_not_implemented(missing)),
+            }
+        }
+    }
+}
+
+use std::fmt::{self, Display, Formatter};
+
+impl Display for DbTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string_pretty(&self).unwrap())
+    }
+}
+
+/// Save the database tables and rows as `dbtables.json` and `dbrows.json`. Exposed as `--dump db_tables`.
+pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
+    fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.to_path_buf()))?;
+
+    let tables = DbTable::dump_all(config)?.into_values().collect::<Vec<_>>();
+    let path = path!(&config.output / "dbtables.json");
+    crate::cli::write_serialized(path, &tables, config)?;
+
+    let rows = DbRow::dump_all(config)?.into_values().collect::<Vec<_>>();
+    let path = path!(&config.output / "dbrows.json");
+    crate::cli::write_serialized(path, &rows, config)?;
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "rs3"))]
+mod tests {
+    use super::*;
+    use crate::cli::Config;
+
+    #[test]
+    fn rows_match_tables() -> CacheResult<()> {
+        let config = Config::env();
+
+        let tables = DbTable::dump_all(&config)?;
+        assert!(tables.values().any(|table| table.columns.is_some()));
+
+        for row in DbRow::dump_all(&config)?.values() {
+            for (column, fields) in row.data.iter().flatten() {
+                for field in fields {
+                    let matches = tables
+                        .values()
+                        .filter_map(|t| t.columns.as_ref()?.get(column))
+                        .any(|c| c.types.len() == field.len());
+                    assert!(matches, "row {} has no table with a matching column {column}", row.id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
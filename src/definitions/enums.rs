@@ -1,12 +1,7 @@
 //! Describes the properties of enums.
 #![allow(non_camel_case_types, missing_docs)]
 
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-    iter,
-};
+use std::{collections::BTreeMap, fs, iter};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -330,10 +325,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     let mut enums = Enum::dump_all(config)?.into_values().collect::<Vec<_>>();
     enums.sort_unstable_by_key(|loc| loc.id);
     let path = path!(config.output / "enums.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-
-    let data = serde_json::to_string_pretty(&enums).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &enums, config)?;
 
     Ok(())
 }
@@ -1,10 +1,6 @@
 //! Describes the properties of items.
 
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::BTreeMap, fs};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -365,9 +361,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     item_configs.sort_unstable_by_key(|loc| loc.id);
 
     let path = path!(config.output / "item_configs.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&item_configs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &item_configs, config)?;
 
     Ok(())
 }
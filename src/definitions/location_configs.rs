@@ -1,7 +1,6 @@
 use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
+    collections::{BTreeMap, BTreeSet},
+    fs,
 };
 
 use bytes::{Buf, Bytes};
@@ -18,7 +17,10 @@ use serde::Serialize;
 
 #[cfg(any(feature = "rs3", feature = "osrs"))]
 use crate::definitions::indextype::IndexType;
-use crate::structures::paramtable::ParamTable;
+use crate::{
+    definitions::opcode_features::{self, Definition},
+    structures::paramtable::ParamTable,
+};
 
 /// Describes the properties of a given [`Location`](crate::definitions::locations::Location).
 
@@ -217,6 +219,83 @@ impl LocationConfig {
         Ok(locations)
     }
 
+    /// Returns the [location configurations](LocationConfig) with the given `ids`, without decoding any others.
+    ///
+    /// Ids without a configuration are left out.
+    #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
+    pub fn dump_some(config: &crate::cli::Config, ids: &BTreeSet<u32>) -> CacheResult<BTreeMap<u32, Self>> {
+        let index = CacheIndex::new(IndexType::LOC_CONFIG, config.input.clone())?;
+
+        let mut locations = BTreeMap::new();
+        for archive_id in ids.iter().map(|id| id >> 8).collect::<BTreeSet<_>>() {
+            if index.metadatas().get(&archive_id).is_none() {
+                continue;
+            }
+            for (file_id, file) in index.archive(archive_id)?.take_files() {
+                let id = archive_id << 8 | file_id;
+                if ids.contains(&id) {
+                    locations.insert(id, Self::deserialize(id, file).map_err(|e| e.add_context_id(id))?);
+                }
+            }
+        }
+        Ok(locations)
+    }
+
+    /// Returns the [location configurations](LocationConfig) with the given `ids`, without decoding any others.
+    ///
+    /// Ids without a configuration are left out.
+    #[cfg(all(feature = "osrs", not(feature = "2008_3_shim"), not(feature = "legacy")))]
+    pub fn dump_some(config: &crate::cli::Config, ids: &BTreeSet<u32>) -> CacheResult<BTreeMap<u32, Self>> {
+        use crate::definitions::indextype::ConfigType;
+
+        let locations = CacheIndex::new(IndexType::CONFIG, config.input.clone())?
+            .archive(ConfigType::LOC_CONFIG)?
+            .take_files()
+            .into_iter()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(id, file)| Self::deserialize(id, file).map(|item| (id, item)).map_err(|e| e.add_context_id(id)))
+            .collect::<Result<BTreeMap<u32, Self>, ReadError>>()?;
+        Ok(locations)
+    }
+
+    /// Returns the [location configurations](LocationConfig) with the given `ids`, without decoding any others.
+    ///
+    /// Ids without a configuration are left out.
+    #[cfg(feature = "legacy")]
+    pub fn dump_some(config: &crate::cli::Config, ids: &BTreeSet<u32>) -> CacheResult<BTreeMap<u32, Self>> {
+        let cache = CacheIndex::new(0, config.input.clone())?;
+        let archive = cache.archive(2)?;
+        let mut file = archive.file_named("loc.dat")?;
+
+        let _count = file.try_get_u16()?;
+        let mut offset_data = archive.file_named("loc.idx")?;
+
+        let mut locations = BTreeMap::new();
+
+        let len = offset_data.try_get_u16()?;
+        for id in 0..len as u32 {
+            let piece_len = offset_data.try_get_u16()?;
+            let data = file.split_to(piece_len as usize);
+            if ids.contains(&id) {
+                locations.insert(id, LocationConfig::deserialize(id, data)?);
+            }
+        }
+
+        Ok(locations)
+    }
+
+    /// Returns the ids of all [location configurations](LocationConfig) whose name contains `query`, ignoring case.
+    ///
+    /// Configurations without a name never match.
+    pub fn find_by_name(configs: &BTreeMap<u32, Self>, query: &str) -> Vec<u32> {
+        let query = query.to_lowercase();
+        configs
+            .iter()
+            .filter(|(_, loc)| loc.name.as_ref().map_or(false, |name| name.to_lowercase().contains(&query)))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
     fn deserialize(id: u32, mut buffer: Bytes) -> Result<Self, ReadError> {
         let mut loc = Self { id, ..Default::default() };
 
@@ -392,7 +471,7 @@ impl LocationConfig {
                         loc.unknown_204 = Some(out)
                     }
                     249 => loc.params = Some(ParamTable::deserialize(&mut buffer)),
-                    missing => Err(ReadError::opcode_not_implemented(missing))?,
+                    missing => Err(opcode_features::opcode_error(Definition::LocationConfig, missing))?,
                 }
             };
             if let Err(e) = read {
@@ -870,9 +949,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.to_path_buf()))?;
     let loc_configs = LocationConfig::dump_all(config)?.into_values().collect::<Vec<_>>();
     let path = path!(config.output / "location_configs.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&loc_configs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &loc_configs, config)?;
 
     Ok(())
 }
@@ -885,13 +962,7 @@ pub fn export_each(config: &crate::cli::Config) -> CacheResult<()> {
     let configs = LocationConfig::dump_all(config)?;
     configs.into_iter().par_bridge().try_for_each(|(id, location_config)| {
         let path = path!(&folder / format!("{id}.json"));
-        let mut file = match File::create(&path) {
-            Ok(f) => f,
-            Err(e) => return Err(CacheError::io(e, path)),
-        };
-        let data = serde_json::to_string_pretty(&location_config).unwrap();
-        file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
-        Ok(())
+        crate::cli::write_serialized(path, &location_config, config)
     })?;
 
     Ok(())
@@ -968,6 +1039,18 @@ mod map_tests {
         Ok(())
     }
 
+    #[test]
+    fn find_swamp() -> CacheResult<()> {
+        let config = Config::env();
+
+        let loc_config = LocationConfig::dump_all(&config)?;
+        let ids = LocationConfig::find_by_name(&loc_config, "sWAMP");
+        assert!(ids.contains(&3263), "{ids:?}");
+        assert!(LocationConfig::find_by_name(&loc_config, "").len() < loc_config.len());
+
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "rs3")]
     fn check_paramtable() -> CacheResult<()> {
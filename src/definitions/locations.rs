@@ -1,12 +1,12 @@
 use std::hash::Hash;
 
-use bytes::{Buf, Bytes};
+use bytes::Bytes;
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 use rs3cache_backend::buf::BufExtra;
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Serialize, Serializer};
 
-use crate::definitions::tiles::TileArray;
+use crate::{cache::error::CacheResult, definitions::tiles::TileArray};
 /// Describes whether this location is on the contained plane.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Watery {
@@ -69,7 +69,8 @@ impl IntoPy<PyObject> for Watery {
 #[allow(missing_docs)]
 #[cfg_attr(feature = "pyo3", pyclass(frozen, get_all))]
 #[serde_with::skip_serializing_none]
-#[derive(Serialize, Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[serde(into = "LocationRepr", from = "LocationRepr")]
 pub struct Location {
     /// The plane a.k.a elevation.
     ///
@@ -107,29 +108,103 @@ pub struct Location {
     pub rotation: u8,
 }
 
+/// How a [`Location`] is (de)serialized.
+///
+/// Its [`plane`](Location::plane) is the plane it is actually on, like [`Watery`] is serialized,
+/// and `watery` tells which [`Watery`] variant it was, so that it can be read back.
+#[derive(Serialize, Deserialize)]
+struct LocationRepr {
+    plane: i8,
+    #[serde(default, skip_serializing_if = "is_false")]
+    watery: bool,
+    i: u8,
+    j: u8,
+    x: u8,
+    y: u8,
+    id: u32,
+    r#type: u8,
+    rotation: u8,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl From<Location> for LocationRepr {
+    fn from(location: Location) -> Self {
+        let (plane, watery) = match location.plane {
+            Watery::True(value) => (value as i8 - 1, true),
+            Watery::False(value) => (value as i8, false),
+        };
+        Self {
+            plane,
+            watery,
+            i: location.i,
+            j: location.j,
+            x: location.x,
+            y: location.y,
+            id: location.id,
+            r#type: location.r#type,
+            rotation: location.rotation,
+        }
+    }
+}
+
+impl From<LocationRepr> for Location {
+    fn from(repr: LocationRepr) -> Self {
+        let plane = if repr.watery {
+            Watery::True((repr.plane + 1) as u8)
+        } else {
+            Watery::False(repr.plane as u8)
+        };
+        Self {
+            plane,
+            i: repr.i,
+            j: repr.j,
+            x: repr.x,
+            y: repr.y,
+            id: repr.id,
+            r#type: repr.r#type,
+            rotation: repr.rotation,
+        }
+    }
+}
+
 impl Location {
+    /// The planes this location is on, as given by [`Watery::matches`].
+    ///
+    /// A location is on at most one plane; this is empty for watery locations below plane 0.
+    pub fn planes(&self) -> impl Iterator<Item = u8> {
+        let plane = self.plane;
+        (0..4).filter(move |p| plane.matches(p))
+    }
+
     // todo: fix this with water tiles
     #[cfg(any(feature = "rs3", feature = "2013_4_shim"))]
-    pub(crate) fn dump_water_locations(i: u8, j: u8, buffer: Bytes) -> Vec<Self> {
+    pub(crate) fn dump_water_locations(i: u8, j: u8, buffer: Bytes) -> CacheResult<Vec<Self>> {
         let blanks = TileArray::default((4, 64, 64));
         Self::dump(i, j, &blanks, buffer)
     }
 
     /// Constructor for [`Location`].
-    pub fn dump(i: u8, j: u8, tiles: &TileArray, mut buffer: Bytes) -> Vec<Self> {
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReadError`](rs3cache_backend::buf::ReadError) if the buffer is truncated.
+    pub fn dump(i: u8, j: u8, tiles: &TileArray, mut buffer: Bytes) -> CacheResult<Vec<Self>> {
         let mut locations = Vec::new();
 
         let mut id: i32 = -1;
 
         loop {
-            match buffer.get_smarts() as i32 {
-                0 => break locations,
+            match buffer.try_get_smarts()? as i32 {
+                0 => break Ok(locations),
                 id_increment => {
                     id += id_increment;
 
                     let mut location = 0;
                     loop {
-                        match buffer.get_unsigned_smart() {
+                        match buffer.try_get_unsigned_smart()? {
                             0 => break,
                             location_increment => {
                                 location += location_increment - 1;
@@ -138,41 +213,41 @@ impl Location {
                                 let x = (location >> 6 & 0x3F) as u8;
                                 let y = (location & 0x3F) as u8;
 
-                                let data = buffer.get_u8();
+                                let data = buffer.try_get_u8()?;
                                 let r#type = data >> 2 & 0x1F;
                                 let rotation = data & 0x3;
 
                                 // some objects have offsets; not using this data atm
                                 #[cfg(feature = "rs3")]
                                 if data >= 0x80 {
-                                    let sub_data = buffer.get_u8();
+                                    let sub_data = buffer.try_get_u8()?;
                                     if sub_data != 0 {
                                         if sub_data & 0x1 != 0 {
-                                            buffer.get_u16();
-                                            buffer.get_u16();
-                                            buffer.get_u16();
-                                            buffer.get_u16();
+                                            buffer.try_get_u16()?;
+                                            buffer.try_get_u16()?;
+                                            buffer.try_get_u16()?;
+                                            buffer.try_get_u16()?;
                                         }
                                         if sub_data & 0x2 != 0 {
-                                            buffer.get_u16();
+                                            buffer.try_get_u16()?;
                                         }
                                         if sub_data & 0x4 != 0 {
-                                            buffer.get_u16();
+                                            buffer.try_get_u16()?;
                                         }
                                         if sub_data & 0x8 != 0 {
-                                            buffer.get_u16();
+                                            buffer.try_get_u16()?;
                                         }
                                         if sub_data & 0x10 != 0 {
-                                            buffer.get_u16();
+                                            buffer.try_get_u16()?;
                                         } else {
                                             if sub_data & 0x20 != 0 {
-                                                buffer.get_u16();
+                                                buffer.try_get_u16()?;
                                             }
                                             if sub_data & 0x40 != 0 {
-                                                buffer.get_u16();
+                                                buffer.try_get_u16()?;
                                             }
                                             if sub_data & 0x80 != 0 {
-                                                buffer.get_u16();
+                                                buffer.try_get_u16()?;
                                             }
                                         }
                                     }
@@ -235,3 +310,66 @@ impl ToPyObject for Location {
         (*self).into_py(py)
     }
 }
+
+#[cfg(test)]
+mod location_tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let location = Location {
+            plane: Watery::False(1),
+            i: 50,
+            j: 50,
+            x: 3,
+            y: 60,
+            id: 1276,
+            r#type: 10,
+            rotation: 2,
+        };
+        let json = serde_json::to_string(&location).unwrap();
+        assert_eq!(serde_json::from_str::<Location>(&json).unwrap(), location);
+
+        for plane in [Watery::True(0), Watery::True(2), Watery::False(0), Watery::False(3)] {
+            let location = Location { plane, ..location };
+            let json = serde_json::to_string(&location).unwrap();
+            assert_eq!(serde_json::from_str::<Location>(&json).unwrap(), location, "{json}");
+        }
+
+        // The plane is still the plane the location is on.
+        let json = serde_json::to_value(Location {
+            plane: Watery::True(2),
+            ..location
+        })
+        .unwrap();
+        assert_eq!(json["plane"], 1);
+        assert_eq!(json["watery"], true);
+    }
+
+    #[test]
+    fn planes() {
+        let location = Location {
+            plane: Watery::False(2),
+            i: 50,
+            j: 50,
+            x: 3,
+            y: 60,
+            id: 1276,
+            r#type: 10,
+            rotation: 2,
+        };
+        assert_eq!(location.planes().collect::<Vec<_>>(), [2]);
+
+        let watery = Location {
+            plane: Watery::True(2),
+            ..location
+        };
+        assert_eq!(watery.planes().collect::<Vec<_>>(), [1]);
+
+        let below = Location {
+            plane: Watery::True(0),
+            ..location
+        };
+        assert_eq!(below.planes().count(), 0);
+    }
+}
@@ -13,6 +13,11 @@ pub struct MapScene {
     pub id: u32,
     /// A reference to the sprite.
     pub sprite_id: Option<u32>,
+    /// Whether the sprite is vertically centred on the corner of the tile it is drawn on.
+    ///
+    /// None of the opcodes of a mapscene are known to describe this, so it depends on the cache instead:
+    /// rs3 draws its mapscenes like this, while earlier caches hang them down from the corner.
+    pub centred: bool,
 
     op_2: Option<u32>,
 
@@ -35,7 +40,11 @@ impl MapScene {
     }
 
     fn deserialize(id: u32, mut buffer: Bytes) -> MapScene {
-        let mut mapscene = MapScene { id, ..Default::default() };
+        let mut mapscene = MapScene {
+            id,
+            centred: cfg!(feature = "rs3"),
+            ..Default::default()
+        };
 
         loop {
             let opcode = buffer.get_u8();
@@ -59,6 +68,13 @@ impl MapScene {
 mod mapscene_tests {
     use super::*;
 
+    #[test]
+    fn only_rs3_mapscenes_are_centred() {
+        let mapscene = MapScene::deserialize(5, Bytes::from_static(&[1, 0, 7, 0]));
+        assert_eq!(mapscene.sprite_id, Some(7));
+        assert_eq!(mapscene.centred, cfg!(feature = "rs3"));
+    }
+
     #[test]
     fn check_1612() -> CacheResult<()> {
         let config = crate::cli::Config::env();
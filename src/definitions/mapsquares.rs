@@ -14,32 +14,38 @@
 mod iterator;
 
 use std::{
-    collections::{hash_map, HashMap},
+    collections::{hash_map, BTreeMap, BTreeSet, HashMap, HashSet},
     fs::{self, File},
-    io::Write,
+    io::{BufWriter, Write},
     iter::Zip,
     ops::Range,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::SystemTime,
 };
 
 use itertools::{iproduct, Product};
-use ndarray::{iter::LanesIter, s, Axis, Dim};
+use ndarray::{iter::LanesIter, s, ArrayView1, Axis, Dim};
 use path_macro::path;
 use rayon::iter::{ParallelBridge, ParallelIterator};
+use serde::Serialize;
 #[cfg(any(feature = "rs3", feature = "2013_4_shim"))]
-use {crate::cache::arc::Archive, crate::definitions::indextype::MapFileType, bytes::Buf, rs3cache_utils::lazy::Lazy};
+use {crate::cache::arc::Archive, crate::cache::buf::BufExtra, crate::definitions::indextype::MapFileType, rs3cache_utils::lazy::Lazy};
 
 pub use self::iterator::*;
 #[cfg(all(feature = "osrs", not(feature = "2013_4_shim")))]
 use crate::cache::xtea::Xtea;
 use crate::{
     cache::{
-        error::{CacheError, CacheResult},
-        index::{CacheIndex, Initial},
+        error::{CacheError, CacheErrorKind, CacheResult},
+        index::{CacheIndex, CachePath, Initial},
     },
     definitions::{
+        location_configs::LocationConfig,
         locations::Location,
         tiles::{Tile, TileArray},
     },
+    types::coordinate::Coordinate,
     utils::rangeclamp::RangeClamp,
 };
 
@@ -73,6 +79,9 @@ pub struct MapSquare {
     /// Locations can overlap on surrounding mapsquares.
     #[cfg(any(feature = "rs3", feature = "2013_4_shim"))]
     water_locations: Lazy<(CacheResult<bytes::Bytes>, u8, u8), Vec<Location>, CacheError>,
+
+    /// The version of the archive this was read from, if the cache records it.
+    version: Option<i32>,
 }
 
 /// Iterator over a columns of planes with their x, y coordinates
@@ -97,28 +106,33 @@ impl MapSquare {
     pub fn new(i: u8, j: u8, config: &crate::cli::Config) -> CacheResult<MapSquare> {
         assert!(i < 0x7F, "Index out of range.");
         let archive_id = (i as u32) | (j as u32) << 7;
-        let archive = CacheIndex::new(crate::definitions::indextype::IndexType::MAPSV2, config.input.clone())?.archive(archive_id)?;
-        Ok(Self::from_archive(archive))
+        let index = CacheIndex::new(crate::definitions::indextype::IndexType::MAPSV2, config.input.clone())?;
+        let version = index.metadatas().get(&archive_id).map(|metadata| metadata.version);
+        Ok(Self::from_archive(index.archive(archive_id)?, version))
     }
 
     #[cfg(all(feature = "osrs", not(feature = "2013_4_shim")))]
-    fn new(index: &CacheIndex<Initial>, xtea: Option<Xtea>, land: u32, tiles: u32, env: Option<u32>, i: u8, j: u8) -> CacheResult<MapSquare> {
+    fn new(index: &CacheIndex<Initial>, xtea: Option<Xtea>, land: u32, tiles_id: u32, env: Option<u32>, i: u8, j: u8) -> CacheResult<MapSquare> {
         let land = index.archive_with_xtea(land, xtea).and_then(|arch| arch.file(&0));
-        let mut tile_bytes = index.archive(tiles)?.file(&0)?;
+        let mut tile_bytes = index.archive(tiles_id)?.file(&0)?;
         let _env = env.map(|k| index.archive(k));
 
         let tiles = Tile::dump(&mut tile_bytes);
-        let locations = match land {
-            Ok(land) => Ok(Location::dump(i, j, &tiles, land)),
-            // most likely, anyway...
-            Err(_) => Err(CacheError::xtea_absent(i, j)),
+        let locations = match (land, &tiles) {
+            (Ok(land), Ok(tiles)) => Location::dump(i, j, tiles, land),
+            (Ok(_), Err(e)) => Err(e.clone()),
+            // Without a key the archive cannot be decrypted, so that is what went wrong, most likely.
+            (Err(_), _) if xtea.is_none() => Err(CacheError::xtea_absent(i, j)),
+            (Err(e), _) => Err(e),
         };
+        let version = index.metadatas().get(&tiles_id).map(|metadata| metadata.version);
 
         Ok(MapSquare {
             i,
             j,
-            tiles: Ok(tiles),
+            tiles,
             locations,
+            version,
         })
     }
 
@@ -128,34 +142,47 @@ impl MapSquare {
         let mut tile_bytes = index.archive(map)?.file(&0)?;
 
         let tiles = Tile::dump(&mut tile_bytes);
-        let locations = Location::dump(i, j, &tiles, land);
+        let locations = match &tiles {
+            Ok(tiles) => Location::dump(i, j, tiles, land),
+            Err(e) => Err(e.clone()),
+        };
 
         Ok(MapSquare {
             i,
             j,
-            tiles: Ok(tiles),
-            locations: Ok(locations),
+            tiles,
+            locations,
+            version: None,
         })
     }
 
     #[cfg(any(feature = "rs3", feature = "2013_4_shim"))]
-    pub(crate) fn from_archive(archive: Archive) -> MapSquare {
+    pub(crate) fn from_archive(archive: Archive, version: Option<i32>) -> MapSquare {
         let i = (archive.archive_id() & 0x7F) as u8;
         let j = (archive.archive_id() >> 7) as u8;
         let mut tile_bytes = archive.file(&MapFileType::TILES);
 
         let (tiles, members, locations) = match tile_bytes {
-            Ok(ref mut tile_bytes) => {
-                let tiles = Tile::dump(tile_bytes);
-                let members = tile_bytes.get_u64();
-                let locations = archive.file(&MapFileType::LOCATIONS).map(|file| Location::dump(i, j, &tiles, file));
-                (Ok(tiles), Ok(members), locations)
+            Ok(ref mut tile_bytes) => match Tile::dump(tile_bytes) {
+                Ok(tiles) => {
+                    let members = tile_bytes.try_get_array().map(u64::from_be_bytes).map_err(CacheError::from);
+                    let locations = archive.file(&MapFileType::LOCATIONS).and_then(|file| Location::dump(i, j, &tiles, file));
+                    (Ok(tiles), members, locations)
+                }
+                Err(e) => {
+                    let locations = archive.file(&MapFileType::LOCATIONS).and(Err(e.clone()));
+                    (Err(e.clone()), Err(e), locations)
+                }
+            },
+            // If the locations file is missing as well, report that rather than the missing tiles.
+            Err(e) => {
+                let locations = archive.file(&MapFileType::LOCATIONS).and(Err(e.clone()));
+                (Err(e.clone()), Err(e), locations)
             }
-            Err(e) => (Err(e.clone()), Err(e.clone()), Err(e)),
         };
 
         let bytes = archive.file(&MapFileType::WATER_LOCATIONS);
-        let water_locations = Lazy::new((bytes, i, j), |(bytes, i, j)| Ok(Location::dump_water_locations(i, j, bytes?)));
+        let water_locations = Lazy::new((bytes, i, j), |(bytes, i, j)| Location::dump_water_locations(i, j, bytes?));
 
         MapSquare {
             i,
@@ -164,6 +191,7 @@ impl MapSquare {
             members,
             locations,
             water_locations,
+            version,
         }
     }
 
@@ -172,6 +200,19 @@ impl MapSquare {
         Ok(self.tiles()?.lanes(Axis(0)).into_iter().zip(iproduct!(0..64u32, 0..64u32)))
     }
 
+    /// Iterator over a columns of planes with their absolute world x, y coordinates
+    pub fn indexed_columns_world(&self) -> Result<impl Iterator<Item = (ArrayView1<Tile>, (u32, u32))>, CacheError> {
+        let (base_x, base_y) = (self.i as u32 * 64, self.j as u32 * 64);
+        Ok(self.indexed_columns()?.map(move |(column, (x, y))| (column, (base_x + x, base_y + y))))
+    }
+
+    /// The version of the archive this was read from, if the cache records it.
+    ///
+    /// This changes whenever the mapsquare is updated.
+    pub fn version(&self) -> Option<i32> {
+        self.version
+    }
+
     /// Returns a view over the `tiles` field, if present
     pub fn tiles(&self) -> Result<&TileArray, &CacheError> {
         self.tiles.as_ref()
@@ -208,9 +249,9 @@ impl MapSquare {
 pub struct MapSquares {
     index: CacheIndex<Initial>,
     #[cfg(all(feature = "osrs", not(feature = "2013_4_shim")))]
-    mapping: std::collections::BTreeMap<(&'static str, u8, u8), u32>,
+    mapping: BTreeMap<(&'static str, u8, u8), u32>,
     #[cfg(feature = "legacy")]
-    meta: std::collections::BTreeMap<(u8, u8), rs3cache_backend::index::MapsquareMeta>,
+    meta: BTreeMap<(u8, u8), rs3cache_backend::index::MapsquareMeta>,
 }
 
 impl IntoIterator for MapSquares {
@@ -247,13 +288,175 @@ impl IntoIterator for MapSquares {
     }
 }
 
+impl MapSquares {
+    /// Iterates over every [`MapSquare`] together with the mapsquares up to `radius` squares away from it.
+    ///
+    /// Mapsquares are visited column by column, so that each one is only loaded once.
+    /// Neighbours that are missing or fail to load are absent from the [`GroupMapSquare`].
+    pub fn group_iter(self, radius: u8) -> GroupIter {
+        let MapSquareIterator { mapsquares, state } = self.into_iter();
+        let coordinates: BTreeSet<(u8, u8)> = state.collect();
+        let state = coordinates.iter().copied().collect::<Vec<_>>().into_iter();
+
+        GroupIter {
+            mapsquares,
+            radius,
+            coordinates,
+            state,
+            loaded: HashMap::new(),
+        }
+    }
+}
+
+/// Iterates over a [`GroupMapSquare`] for every [`MapSquare`], as returned by [`MapSquares::group_iter`].
+pub struct GroupIter {
+    mapsquares: MapSquares,
+    radius: u8,
+    coordinates: BTreeSet<(u8, u8)>,
+    state: std::vec::IntoIter<(u8, u8)>,
+    loaded: HashMap<(u8, u8), Option<Arc<MapSquare>>>,
+}
+
+impl Iterator for GroupIter {
+    type Item = GroupMapSquare;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (core_i, core_j) = self.state.next()?;
+        let radius = self.radius as i32;
+
+        // Columns are visited in ascending order, so these will not be needed again.
+        self.loaded.retain(|(i, _), _| *i as i32 + radius >= core_i as i32);
+
+        let mapsquares = iproduct!(-radius..=radius, -radius..=radius)
+            .filter_map(|(di, dj)| Some((u8::try_from(core_i as i32 + di).ok()?, u8::try_from(core_j as i32 + dj).ok()?)))
+            .filter(|coordinate| self.coordinates.contains(coordinate))
+            .filter_map(|(i, j)| {
+                self.loaded
+                    .entry((i, j))
+                    .or_insert_with(|| self.mapsquares.get(i, j).ok().map(Arc::new))
+                    .clone()
+            })
+            .map(|sq| ((sq.i, sq.j), sq))
+            .collect();
+
+        Some(GroupMapSquare { core_i, core_j, mapsquares })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.state.size_hint()
+    }
+}
+
+impl ExactSizeIterator for GroupIter {}
+
+/// An entry of the manifest written by [`export_mapsquare_manifest`], as returned by [`MapSquares::manifest`].
+///
+/// This is derived from the cache's metadata, without decoding the mapsquare itself.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct MapSquareManifestEntry {
+    /// The horizontal [`MapSquare`] coordinate.
+    pub i: u8,
+    /// The vertical [`MapSquare`] coordinate.
+    pub j: u8,
+    /// Whether the mapsquare has a tiles file.
+    pub tiles: bool,
+    /// Whether the mapsquare has a locations file.
+    pub locations: bool,
+    /// The archive containing this mapsquare's tiles.
+    #[cfg(any(feature = "rs3", feature = "osrs"))]
+    pub archive_id: u32,
+    /// Whether a xtea key is available to decrypt this mapsquare's locations.
+    #[cfg(all(feature = "osrs", not(feature = "2013_4_shim")))]
+    pub xtea: bool,
+    /// Whether this mapsquare is accessible to free players.
+    #[cfg(feature = "legacy")]
+    pub f2p: bool,
+    /// The archive containing this mapsquare's tiles.
+    #[cfg(feature = "legacy")]
+    pub mapfile: u16,
+    /// The archive containing this mapsquare's locations.
+    #[cfg(feature = "legacy")]
+    pub locfile: u16,
+}
+
+/// Coverage statistics of all [`MapSquare`]s in a cache, as returned by [`MapSquares::stats`].
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MapStats {
+    /// The number of mapsquares in the cache.
+    pub total: usize,
+    /// The number of mapsquares whose tiles could be decoded.
+    pub with_tiles: usize,
+    /// The number of mapsquares whose locations could be decoded.
+    pub with_locations: usize,
+    /// The number of mapsquares whose locations are missing, or that could not be decrypted.
+    pub missing_land: usize,
+    /// The number of times each kind of error was encountered,
+    /// counted once for each of a mapsquare's fields that failed.
+    pub errors: BTreeMap<&'static str, usize>,
+}
+
+impl MapSquares {
+    /// Decodes every [`MapSquare`] and reports how many of them are complete.
+    ///
+    /// This is useful to check whether a cache (and its xteas, if any) is complete before exporting.
+    pub fn stats(config: &crate::cli::Config) -> CacheResult<MapStats> {
+        let mut stats = MapStats::default();
+
+        for sq in MapSquares::new(config)? {
+            stats.total += 1;
+            let sq = match sq {
+                Ok(sq) => sq,
+                Err(e) => {
+                    stats.record(&e);
+                    if is_missing_land(&e) {
+                        stats.missing_land += 1;
+                    }
+                    continue;
+                }
+            };
+
+            match sq.tiles() {
+                Ok(_) => stats.with_tiles += 1,
+                Err(e) => stats.record(e),
+            }
+
+            match sq.locations() {
+                Ok(_) => stats.with_locations += 1,
+                Err(e) => {
+                    stats.record(&e);
+                    if is_missing_land(&e) {
+                        stats.missing_land += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+impl MapStats {
+    fn record(&mut self, error: &CacheError) {
+        *self.errors.entry(error.kind().name()).or_default() += 1;
+    }
+}
+
+fn is_missing_land(error: &CacheError) -> bool {
+    match error.kind() {
+        CacheErrorKind::ArchiveNotFoundError(..) | CacheErrorKind::NamedArchiveNotFoundError(..) | CacheErrorKind::FileMissingError(..) => true,
+        #[cfg(feature = "osrs")]
+        CacheErrorKind::XteaError { .. } => true,
+        _ => false,
+    }
+}
+
 /// A group of adjacent [`MapSquare`]s.
 ///
 /// Necessary for operations that need to care about surrounding mapsquares.
 pub struct GroupMapSquare {
     core_i: u8,
     core_j: u8,
-    mapsquares: HashMap<(u8, u8), MapSquare>,
+    mapsquares: HashMap<(u8, u8), Arc<MapSquare>>,
 }
 
 impl GroupMapSquare {
@@ -275,59 +478,186 @@ impl GroupMapSquare {
 
     /// Returns a reference to the central [`MapSquare`].
     pub fn core(&self) -> Option<&MapSquare> {
-        self.mapsquares.get(&(self.core_i, self.core_j))
+        self.get(&(self.core_i, self.core_j))
     }
 
     /// Iterates over all [`MapSquare`]s of `self` in arbitrary order.
-    pub fn iter(&self) -> hash_map::Iter<'_, (u8, u8), MapSquare> {
+    pub fn iter(&self) -> hash_map::Iter<'_, (u8, u8), Arc<MapSquare>> {
         self.mapsquares.iter()
     }
 
     /// Returns a view over a specific [`MapSquare`]..
     pub fn get(&self, key: &(u8, u8)) -> Option<&MapSquare> {
-        self.mapsquares.get(key)
+        self.mapsquares.get(key).map(Arc::as_ref)
     }
 
     /// Returns a view over all tiles within `interp` of the [`Tile`] at `plane, x, y`.
-    pub fn tiles_iter(&self, plane: usize, x: usize, y: usize, interp: isize) -> Box<dyn Iterator<Item = &Tile> + '_> {
+    pub fn tiles_iter(&self, plane: usize, x: usize, y: usize, interp: isize) -> impl Iterator<Item = &Tile> + '_ {
         let low_x = x as isize - interp;
         let upper_x = x as isize + interp + 1;
         let low_y = y as isize - interp;
         let upper_y = y as isize + interp + 1;
 
-        Box::new(
-            self.iter()
-                .filter_map(move |((i, j), sq)| {
-                    sq.tiles().ok().map(|tiles| {
-                        let di = (*i as isize) - (self.core_i as isize);
-                        let dj = (*j as isize) - (self.core_j as isize);
-                        ((di, dj), tiles)
-                    })
+        self.iter()
+            .filter_map(move |((i, j), sq)| {
+                sq.tiles().ok().map(|tiles| {
+                    let di = (*i as isize) - (self.core_i as isize);
+                    let dj = (*j as isize) - (self.core_j as isize);
+                    ((di, dj), tiles)
                 })
-                .flat_map(move |((di, dj), tiles)| {
-                    tiles
-                        .slice(s![
-                            plane,
-                            ((low_x - 64 * di)..(upper_x - 64 * di)).clamp(0, 64),
-                            ((low_y - 64 * dj)..(upper_y - 64 * dj)).clamp(0, 64)
-                        ])
-                        .into_iter()
-                }),
-        )
+            })
+            .flat_map(move |((di, dj), tiles)| {
+                tiles
+                    .slice(s![
+                        plane,
+                        ((low_x - 64 * di)..(upper_x - 64 * di)).clamp(0, 64),
+                        ((low_y - 64 * dj)..(upper_y - 64 * dj)).clamp(0, 64)
+                    ])
+                    .into_iter()
+            })
     }
 
     /// Returns a view over all locations in all [`MapSquare`]s of `self` in arbitrary order.
-    pub fn all_locations_iter(&self) -> Box<dyn Iterator<Item = &Location> + '_> {
-        Box::new(
-            self.iter()
-                .filter_map(|(_k, square)| square.locations().ok())
-                .flat_map(IntoIterator::into_iter),
-        )
+    pub fn all_locations_iter(&self) -> impl Iterator<Item = &Location> + '_ {
+        self.iter()
+            .filter_map(|(_k, square)| square.locations().ok())
+            .flat_map(IntoIterator::into_iter)
+    }
+
+    /// Returns a view over all locations on the tile at `plane, x, y`.
+    ///
+    /// `x` and `y` are relative to the central [`MapSquare`], and may lie in one of its neighbours.
+    /// Locations are on the tile if they [match](crate::definitions::locations::Watery::matches) `plane`.
+    /// Locations that span multiple tiles are only found on their south-western tile.
+    pub fn locations_at(&self, plane: u8, x: isize, y: isize) -> impl Iterator<Item = &Location> + '_ {
+        let i = u8::try_from(self.core_i as isize + x.div_euclid(64)).ok();
+        let j = u8::try_from(self.core_j as isize + y.div_euclid(64)).ok();
+        let x = x.rem_euclid(64) as u8;
+        let y = y.rem_euclid(64) as u8;
+
+        i.zip(j)
+            .and_then(|key| self.get(&key))
+            .and_then(|square| square.locations().ok())
+            .into_iter()
+            .flatten()
+            .filter(move |loc| loc.x == x && loc.y == y && loc.plane.matches(&plane))
+    }
+
+    /// Like [`locations_at`](GroupMapSquare::locations_at), but takes a world coordinate.
+    pub fn locations_at_world(&self, coordinate: Coordinate) -> impl Iterator<Item = &Location> + '_ {
+        let x = coordinate.x as isize - 64 * self.core_i as isize;
+        let y = coordinate.y as isize - 64 * self.core_j as isize;
+        self.locations_at(coordinate.plane, x, y)
     }
 }
 
+#[cfg(test)]
+impl GroupMapSquare {
+    /// A group of only the square at `i, j`, which has flat tiles and the given `locations`.
+    pub fn from_locations(i: u8, j: u8, locations: Vec<Location>) -> Self {
+        let square = MapSquare {
+            i,
+            j,
+            tiles: Ok(TileArray::default((4, 64, 64))),
+            #[cfg(feature = "rs3")]
+            members: Ok(0),
+            locations: Ok(locations),
+            #[cfg(any(feature = "rs3", feature = "2013_4_shim"))]
+            water_locations: Lazy::new((Ok(bytes::Bytes::new()), i, j), |_| Ok(Vec::new())),
+            version: None,
+        };
+        Self {
+            core_i: i,
+            core_j: j,
+            mapsquares: HashMap::from([((i, j), Arc::new(square))]),
+        }
+    }
+}
+
+/// Everything on a single tile, as returned by [`inspect_tile`].
+#[derive(Serialize, Clone, Debug)]
+pub struct TileInspection {
+    /// The world coordinate of the tile.
+    pub coordinate: Coordinate,
+    /// The tile itself.
+    pub tile: Tile,
+    /// The locations on the tile.
+    pub locations: Vec<InspectedLocation>,
+}
+
+/// A [`Location`] together with the name of its [`LocationConfig`].
+#[derive(Serialize, Clone, Debug)]
+pub struct InspectedLocation {
+    /// The location itself.
+    #[serde(flatten)]
+    pub location: Location,
+    /// The name of the location, if it has one.
+    pub name: Option<String>,
+}
+
+/// Looks up the [`Tile`] and [`Location`]s at the world coordinate `plane, x, y`.
+///
+/// Like [`GroupMapSquare::locations_at`], locations that span multiple tiles are only found on their south-western tile.
+///
+/// # Errors
+///
+/// Raises [`PlaneError`](CacheErrorKind::PlaneError) if `plane` is not in the range `0..4`.
+pub fn inspect_tile(config: &crate::cli::Config, x: u16, y: u16, plane: u8) -> CacheResult<TileInspection> {
+    if plane >= 4 {
+        return Err(CacheError::plane(plane));
+    }
+    let coordinate = Coordinate { plane, x, y };
+    let (i, j) = ((x / 64) as u8, (y / 64) as u8);
+    let (local_x, local_y) = ((x % 64) as u8, (y % 64) as u8);
+
+    let square = MapSquares::new(config)?.get(i, j)?;
+    let tile = square.tiles()?[[plane as usize, local_x as usize, local_y as usize]];
+
+    let on_tile: Vec<Location> = square
+        .locations()?
+        .iter()
+        .filter(|loc| loc.x == local_x && loc.y == local_y && loc.plane.matches(&plane))
+        .copied()
+        .collect();
+    let location_configs = LocationConfig::dump_some(config, &on_tile.iter().map(|loc| loc.id).collect())?;
+    let locations = on_tile
+        .into_iter()
+        .map(|location| InspectedLocation {
+            location,
+            name: location_configs
+                .get(&location.id)
+                .and_then(|conf| conf.name.as_deref())
+                .map(str::to_owned),
+        })
+        .collect();
+
+    Ok(TileInspection { coordinate, tile, locations })
+}
+
+/// Returns the ids of all locations in the map that have no [`LocationConfig`], in ascending order.
+///
+/// Rendering the map panics on such locations, so this can be used to check a cache beforehand.
+/// Squares that cannot be read, or whose locations cannot be read, are ignored.
+pub fn validate_consistency(config: &crate::cli::Config) -> CacheResult<Vec<u32>> {
+    let location_configs = LocationConfig::dump_all(config)?;
+
+    let mut missing = BTreeSet::new();
+    for square in MapSquares::new(config)? {
+        if let Ok(locations) = square.and_then(MapSquare::take_locations) {
+            missing.extend(locations.into_iter().map(|loc| loc.id).filter(|id| !location_configs.contains_key(id)));
+        }
+    }
+    Ok(missing.into_iter().collect())
+}
+
 /// Saves all occurences of every object id as a `json` file to the folder `out/data/rs3/locations`.
+///
+/// Ids given by `--skip-location-ids` are not saved.
 pub fn export_locations_by_id(config: &crate::cli::Config) -> CacheResult<()> {
+    // This used to always skip id 83, presumably because it is placed so often that its file is unwieldy.
+    // That silently made the export incomplete, so it is opt-in now.
+    let skip_ids: HashSet<u32> = config.skip_location_ids.iter().copied().collect();
+
     let out = path_macro::path!(config.output / "locations");
 
     fs::create_dir_all(&out).map_err(|e| CacheError::io(e, out.clone()))?;
@@ -361,10 +691,8 @@ pub fn export_locations_by_id(config: &crate::cli::Config) -> CacheResult<()> {
         })
         .par_bridge()
         .for_each(|(id, id_locs)| {
-            if !id_locs.is_empty() && id != 83 {
-                let mut file = File::create(path!(&out / format!("{id}.json"))).unwrap();
-                let data = serde_json::to_string_pretty(&id_locs).unwrap();
-                file.write_all(data.as_bytes()).unwrap();
+            if !id_locs.is_empty() && !skip_ids.contains(&id) {
+                crate::cli::write_serialized(path!(&out / format!("{id}.json")), &id_locs, config).unwrap();
             }
         });
 
@@ -372,51 +700,164 @@ pub fn export_locations_by_id(config: &crate::cli::Config) -> CacheResult<()> {
 }
 
 /// Saves all occurences of every object id as a `json` file to the folder `out/data/rs3/locations`.
+///
+/// With `--include-version`, the archive version of every square is saved to `versions.json` in the same folder.
 pub fn export_locations_by_square(config: &crate::cli::Config) -> CacheResult<()> {
     let out = path_macro::path!(config.output / "locations");
 
     fs::create_dir_all(&out).map_err(|e| CacheError::io(e, out.clone()))?;
-    MapSquares::new(config)?.into_iter().par_bridge().for_each(|sq| {
+    let cache_modified = config.resume.then(|| cache_modified(&config.input)).flatten();
+
+    let versions: BTreeMap<String, i32> = MapSquares::new(config)?
+        .into_iter()
+        .par_bridge()
+        .filter_map(|sq| {
+            let sq = sq.expect("error deserializing mapsquare");
+            let i = sq.i;
+            let j = sq.j;
+            let version = sq.version().map(|version| (format!("{i}_{j}"), version));
+            let path = path!(&out / format!("{i}_{j}.json"));
+            let output_path = crate::cli::output_path(path.clone(), config);
+            if is_up_to_date(&output_path, cache_modified) {
+                log::debug!("Skipping mapsquare {i}_{j}, {} is up to date", output_path.display());
+                return version;
+            }
+            match sq.take_locations() {
+                Ok(locations) if locations.is_empty() => {}
+                Ok(locations) => crate::cli::write_serialized(path, &locations, config).unwrap(),
+                Err(e) if is_missing_land(&e) => log::debug!("Skipping mapsquare {i}_{j}: {e}"),
+                Err(e) => log::warn!("Skipping mapsquare {i}_{j}: {e}"),
+            }
+            version
+        })
+        .collect();
+
+    if config.include_version {
+        crate::cli::write_serialized(path!(&out / "versions.json"), &versions, config)?;
+    }
+
+    Ok(())
+}
+
+/// Saves the locations of every square to `locations.ndjson`, as newline-delimited json with one [`Location`] per line.
+///
+/// Each location has the `i` and `j` of its square. The locations of a square are on consecutive lines,
+/// but squares are in no particular order.
+pub fn export_locations_combined(config: &crate::cli::Config) -> CacheResult<()> {
+    fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.clone()))?;
+
+    let path = path!(config.output / "locations.ndjson");
+    let file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
+    let writer = Mutex::new(BufWriter::new(file));
+
+    MapSquares::new(config)?.into_iter().par_bridge().try_for_each(|sq| -> CacheResult<()> {
         let sq = sq.expect("error deserializing mapsquare");
-        let i = sq.i;
-        let j = sq.j;
-        if let Ok(locations) = sq.take_locations() {
-            if !locations.is_empty() {
-                let mut file = File::create(path!(&out / format!("{i}_{j}.json"))).unwrap();
-                let data = serde_json::to_string_pretty(&locations).unwrap();
-                file.write_all(data.as_bytes()).unwrap();
+        let (i, j) = (sq.i, sq.j);
+        match sq.take_locations() {
+            Ok(locations) => {
+                let mut lines = Vec::new();
+                for location in &locations {
+                    serde_json::to_writer(&mut lines, location).expect("locations always serialize");
+                    lines.push(b'\n');
+                }
+                writer.lock().unwrap().write_all(&lines).map_err(|e| CacheError::io(e, path.clone()))?;
             }
+            Err(e) if is_missing_land(&e) => log::debug!("Skipping mapsquare {i}_{j}: {e}"),
+            Err(e) => log::warn!("Skipping mapsquare {i}_{j}: {e}"),
         }
-    });
+        Ok(())
+    })?;
+
+    writer.into_inner().unwrap().flush().map_err(|e| CacheError::io(e, path))?;
 
     Ok(())
 }
 
 /// Saves all occurences of every object id as a `json` file to the folder `out/data/rs3/locations`.
+///
+/// With `--include-version`, the archive version of every square is saved to `versions.json` in the same folder.
 pub fn export_tiles_by_square(config: &crate::cli::Config) -> CacheResult<()> {
     let out = path_macro::path!(config.output / "tiles");
 
     fs::create_dir_all(&out).map_err(|e| CacheError::io(e, out.clone()))?;
-    MapSquares::new(config)?.into_iter().par_bridge().for_each(|sq| {
-        let sq = sq.expect("error deserializing mapsquare");
-        let i = sq.i;
-        let j = sq.j;
-        if let Ok(tiles) = sq.take_tiles() {
-            if !tiles.is_empty() {
-                let mut file = File::create(path!(&out / format!("{i}_{j}.json"))).unwrap();
-                let data = serde_json::to_string_pretty(&tiles).unwrap();
-                file.write_all(data.as_bytes()).unwrap();
+    let cache_modified = config.resume.then(|| cache_modified(&config.input)).flatten();
+    let planes = config.planes();
+
+    let versions: BTreeMap<String, i32> = MapSquares::new(config)?
+        .into_iter()
+        .par_bridge()
+        .filter_map(|sq| {
+            let sq = sq.expect("error deserializing mapsquare");
+            let i = sq.i;
+            let j = sq.j;
+            let version = sq.version().map(|version| (format!("{i}_{j}"), version));
+            let path = path!(&out / format!("{i}_{j}.json"));
+            let output_path = crate::cli::output_path(path.clone(), config);
+            if is_up_to_date(&output_path, cache_modified) {
+                log::debug!("Skipping mapsquare {i}_{j}, {} is up to date", output_path.display());
+                return version;
             }
-        }
-    });
+            match sq.take_tiles() {
+                Ok(tiles) if tiles.is_empty() => {}
+                Ok(tiles) if planes == (0..=3) => crate::cli::write_serialized(path, &tiles, config).unwrap(),
+                Ok(tiles) => crate::cli::write_serialized(path, &tiles.slice(s![planes.clone(), .., ..]), config).unwrap(),
+                Err(e) if is_missing_land(&e) => log::debug!("Skipping mapsquare {i}_{j}: {e}"),
+                Err(e) => log::warn!("Skipping mapsquare {i}_{j}: {e}"),
+            }
+            version
+        })
+        .collect();
+
+    if config.include_version {
+        crate::cli::write_serialized(path!(&out / "versions.json"), &versions, config)?;
+    }
+
+    Ok(())
+}
+
+/// Saves an overview of every mapsquare in the cache to `mapsquares.json`.
+///
+/// See [`MapSquareManifestEntry`] for what is recorded about each mapsquare.
+pub fn export_mapsquare_manifest(config: &crate::cli::Config) -> CacheResult<()> {
+    fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.clone()))?;
+
+    let manifest = MapSquares::new(config)?.manifest();
+    crate::cli::write_serialized(path!(config.output / "mapsquares.json"), &manifest, config)?;
 
     Ok(())
 }
 
+/// The newest modification time of the files in the cache folder, looking one folder deep.
+fn cache_modified(input: &CachePath) -> Option<SystemTime> {
+    fs::read_dir(input)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .flat_map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => fs::read_dir(entry.path()).into_iter().flatten().flatten().collect(),
+            _ => vec![entry],
+        })
+        .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+        .max()
+}
+
+/// Whether `path` was written by an earlier, resumed, export that happened after the cache was last modified.
+///
+/// Exporters write to a temporary file first, so a file that exists was written completely.
+/// Empty files are never up to date.
+fn is_up_to_date(path: &Path, cache_modified: Option<SystemTime>) -> bool {
+    match (fs::metadata(path), cache_modified) {
+        (Ok(metadata), Some(cache_modified)) => metadata.len() > 0 && metadata.modified().map_or(false, |modified| modified >= cache_modified),
+        _ => false,
+    }
+}
+
 #[cfg(all(test, any(feature = "rs3", feature = "osrs")))]
 mod tests {
     use super::*;
     use crate::cli::Config;
+    #[cfg(feature = "rs3")]
+    use crate::definitions::locations::Watery;
     #[test]
     fn water() -> CacheResult<()> {
         let config = Config::env();
@@ -434,6 +875,153 @@ mod tests {
         }
         panic!("Unable to get some water");
     }
+
+    #[test]
+    fn stats() -> CacheResult<()> {
+        let config = Config::env();
+
+        let stats = MapSquares::stats(&config)?;
+        assert!(stats.total > 0);
+        assert!(stats.with_tiles <= stats.total, "{stats:?}");
+        assert!(stats.with_locations + stats.missing_land <= stats.total, "{stats:?}");
+        Ok(())
+    }
+
+    #[cfg(any(feature = "rs3", feature = "2013_4_shim"))]
+    #[test]
+    fn indexed_columns_world() -> CacheResult<()> {
+        let config = Config::env();
+
+        let square = MapSquare::new(50, 50, &config)?;
+        let local = square.indexed_columns()?.map(|(_, xy)| xy);
+        let world = square.indexed_columns_world()?.map(|(_, xy)| xy);
+        for ((x, y), (world_x, world_y)) in local.zip(world) {
+            assert_eq!((world_x, world_y), (50 * 64 + x, 50 * 64 + y));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn manifest() -> CacheResult<()> {
+        let config = Config::env();
+
+        let manifest = MapSquares::new(&config)?.manifest();
+        let squares = MapSquares::new(&config)?.into_iter().len();
+        assert_eq!(manifest.len(), squares);
+        assert!(manifest.iter().any(|entry| entry.tiles && entry.locations));
+        Ok(())
+    }
+
+    #[test]
+    fn group_iter() -> CacheResult<()> {
+        let config = Config::env();
+
+        let total = MapSquares::new(&config)?.into_iter().len();
+        let groups = MapSquares::new(&config)?.group_iter(1);
+        assert_eq!(groups.len(), total);
+
+        for group in groups.take(50) {
+            for (i, j) in group.mapsquares.keys() {
+                assert!(group.core_i.abs_diff(*i) <= 1 && group.core_j.abs_diff(*j) <= 1);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rs3")]
+    #[test]
+    fn inspect() -> CacheResult<()> {
+        let config = Config::env();
+
+        let square = MapSquares::new(&config)?.get(50, 50)?;
+        let loc = *square.locations()?.iter().find(|loc| matches!(loc.plane, Watery::False(_))).unwrap();
+        let (plane, x, y) = (loc.plane.inner(), 50 * 64 + loc.x as u16, 50 * 64 + loc.y as u16);
+
+        let inspection = inspect_tile(&config, x, y, plane)?;
+        assert_eq!(inspection.coordinate, Coordinate { plane, x, y });
+        assert!(inspection.locations.iter().any(|inspected| inspected.location == loc));
+        Ok(())
+    }
+
+    #[test]
+    fn export_planes() -> CacheResult<()> {
+        let output = std::env::temp_dir().join("rs3cache_planes_test");
+        let config = Config {
+            output: output.clone(),
+            planes: Some(vec![1, 2]),
+            ..Config::env()
+        };
+        export_tiles_by_square(&config)?;
+
+        let path = crate::cli::output_path(path!(output / "tiles" / "50_50.json"), &config);
+        let tiles: serde_json::Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        assert_eq!(tiles["dim"], serde_json::json!([2, 64, 64]));
+
+        fs::remove_dir_all(output).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn inspect_invalid_plane() {
+        let config = Config::env();
+
+        let error = inspect_tile(&config, 50 * 64, 50 * 64, 4).unwrap_err();
+        assert!(matches!(error.kind(), CacheErrorKind::PlaneError(4)), "{error}");
+    }
+
+    #[cfg(feature = "rs3")]
+    #[test]
+    fn locations_at() -> CacheResult<()> {
+        let config = Config::env();
+
+        let group = GroupMapSquareIterator::new_only(-1..=1, -1..=1, vec![(50, 50)], &config)?.next().unwrap();
+        let loc = *group
+            .core()
+            .unwrap()
+            .locations()?
+            .iter()
+            .find(|loc| matches!(loc.plane, Watery::False(_)))
+            .unwrap();
+        let plane = loc.plane.inner();
+
+        assert!(group.locations_at(plane, loc.x as isize, loc.y as isize).any(|l| *l == loc));
+
+        let coordinate = Coordinate {
+            plane,
+            x: 50 * 64 + loc.x as u16,
+            y: 50 * 64 + loc.y as u16,
+        };
+        assert!(group.locations_at_world(coordinate).any(|l| *l == loc));
+
+        // The same tile, seen from the square to the east.
+        let group = GroupMapSquareIterator::new_only(-1..=1, -1..=1, vec![(51, 50)], &config)?.next().unwrap();
+        assert!(group.locations_at(plane, loc.x as isize - 64, loc.y as isize).any(|l| *l == loc));
+        Ok(())
+    }
+
+    #[test]
+    fn consistent() -> CacheResult<()> {
+        let config = Config::env();
+
+        assert_eq!(validate_consistency(&config)?, Vec::<u32>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn resume() {
+        let path = std::env::temp_dir().join("rs3cache_resume_test.json");
+        let before = SystemTime::now() - std::time::Duration::from_secs(60);
+
+        fs::write(&path, b"").unwrap();
+        assert!(!is_up_to_date(&path, Some(before)));
+
+        fs::write(&path, b"[]").unwrap();
+        assert!(is_up_to_date(&path, Some(before)));
+        assert!(!is_up_to_date(&path, None));
+
+        fs::remove_file(&path).unwrap();
+        assert!(!is_up_to_date(&path, Some(before)));
+    }
 }
 
 #[cfg(all(test, feature = "legacy"))]
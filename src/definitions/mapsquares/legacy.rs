@@ -1,5 +1,8 @@
 use core::ops::RangeInclusive;
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use itertools::iproduct;
 use rs3cache_backend::index::MapsquareMeta;
@@ -9,7 +12,7 @@ use crate::{
         error::CacheResult,
         index::{self, CacheIndex},
     },
-    definitions::mapsquares::{GroupMapSquare, MapSquare, MapSquares},
+    definitions::mapsquares::{GroupMapSquare, MapSquare, MapSquareManifestEntry, MapSquares},
 };
 
 impl MapSquares {
@@ -25,6 +28,22 @@ impl MapSquares {
         let sq = MapSquare::new(&self.index, loc, map, i, j)?;
         Ok(sq)
     }
+
+    /// Lists every mapsquare in the cache. See [`MapSquareManifestEntry`].
+    pub fn manifest(&self) -> Vec<MapSquareManifestEntry> {
+        self.meta
+            .iter()
+            .map(|((i, j), meta)| MapSquareManifestEntry {
+                i: *i,
+                j: *j,
+                tiles: self.index.archive(meta.mapfile as u32).is_ok(),
+                locations: self.index.archive(meta.locfile as u32).is_ok(),
+                f2p: meta.f2p,
+                mapfile: meta.mapfile,
+                locfile: meta.locfile,
+            })
+            .collect()
+    }
 }
 
 /// Iterates over all [`MapSquare`]s in arbitrary order.
@@ -88,7 +107,7 @@ impl Iterator for GroupMapSquareIterator {
             let coordinates =
                 iproduct!(self.range_i.clone(), self.range_j.clone()).map(|(di, dj)| ((di + (core_i as i32)) as u8, ((dj + (core_j as i32)) as u8)));
 
-            let mapsquares: HashMap<(u8, u8), MapSquare> = coordinates
+            let mapsquares: HashMap<(u8, u8), Arc<MapSquare>> = coordinates
                 .filter_map(|(i, j)| {
                     if let Some(meta) = self.meta.get(&(i, j)) {
                         let loc = meta.locfile as u32;
@@ -98,7 +117,7 @@ impl Iterator for GroupMapSquareIterator {
                         None
                     }
                 })
-                .map(|sq| ((sq.i, sq.j), sq))
+                .map(|sq| ((sq.i, sq.j), Arc::new(sq)))
                 .collect();
             GroupMapSquare { core_i, core_j, mapsquares }
         })
@@ -1,5 +1,8 @@
 use core::ops::RangeInclusive;
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use itertools::iproduct;
 use rs3cache_backend::error::CacheError;
@@ -11,7 +14,7 @@ use crate::{
     },
     definitions::{
         indextype::{IndexType, MapFileType},
-        mapsquares::{GroupMapSquare, MapSquare, MapSquares},
+        mapsquares::{GroupMapSquare, MapSquare, MapSquareManifestEntry, MapSquares},
     },
 };
 impl MapSquares {
@@ -49,17 +52,40 @@ impl MapSquares {
     }
 
     pub fn get(&self, i: u8, j: u8) -> CacheResult<MapSquare> {
-        let land = self
-            .mapping
-            .get(&("l", i, j))
-            .ok_or_else(|| CacheError::archive_missing(i as u32, j as u32))?;
-        let map = self.mapping.get(&("m", i, j)).unwrap();
+        let land = self.archive_id(MapFileType::LOCATIONS, i, j)?;
+        let map = self.archive_id(MapFileType::TILES, i, j)?;
         let env = self.mapping.get(&("e", i, j)).copied();
         let xtea = self.index.xteas().as_ref().unwrap().get(&(((i as u32) << 8) | j as u32));
 
-        let sq = MapSquare::new(&self.index, xtea.copied(), *land, *map, env, i, j)?;
+        let sq = MapSquare::new(&self.index, xtea.copied(), land, map, env, i, j)?;
         Ok(sq)
     }
+
+    /// The archive id of the file of type `ty` for the mapsquare at `i`, `j`.
+    fn archive_id(&self, ty: &'static str, i: u8, j: u8) -> CacheResult<u32> {
+        self.mapping.get(&(ty, i, j)).copied().ok_or_else(|| {
+            let name = format!("{ty}{i}_{j}");
+            let name_hash = crate::cache::hash::hash_djb2(&name);
+            CacheError::named_archive_missing(self.index.index_id(), name, name_hash)
+        })
+    }
+
+    /// Lists every mapsquare in the cache. See [`MapSquareManifestEntry`].
+    pub fn manifest(&self) -> Vec<MapSquareManifestEntry> {
+        let xteas = self.index.xteas().as_ref();
+        self.mapping
+            .iter()
+            .filter(|((ty, _, _), _)| *ty == MapFileType::TILES)
+            .map(|((_, i, j), archive_id)| MapSquareManifestEntry {
+                i: *i,
+                j: *j,
+                tiles: true,
+                locations: self.mapping.contains_key(&(MapFileType::LOCATIONS, *i, *j)),
+                archive_id: *archive_id,
+                xtea: xteas.map_or(false, |xteas| xteas.contains_key(&(((*i as u32) << 8) | *j as u32))),
+            })
+            .collect()
+    }
 }
 
 /// Iterates over all [`MapSquare`]s in arbitrary order.
@@ -149,18 +175,18 @@ impl Iterator for GroupMapSquareIterator {
             let coordinates =
                 iproduct!(self.range_i.clone(), self.range_j.clone()).map(|(di, dj)| ((di + (core_i as i32)) as u8, ((dj + (core_j as i32)) as u8)));
 
-            let mapsquares: HashMap<(u8, u8), MapSquare> = coordinates
+            let mapsquares: HashMap<(u8, u8), Arc<MapSquare>> = coordinates
                 .filter_map(|(i, j)| {
-                    if let Some(land) = self.mapping.get(&("l", i, j)) {
-                        let map = self.mapping.get(&("m", i, j)).unwrap();
+                    if let Some(land) = self.mapping.get(&("l", i, j)).copied() {
+                        let map = self.mapping.get(&("m", i, j)).copied().unwrap();
                         let env = self.mapping.get(&("e", i, j)).copied();
                         let xtea = self.inner.xteas().as_ref().unwrap().get(&(((i as u32) << 8) | j as u32));
-                        MapSquare::new(&self.inner, xtea.copied(), *land, *map, env, i, j).ok()
+                        MapSquare::new(&self.inner, xtea.copied(), land, map, env, i, j).ok()
                     } else {
                         None
                     }
                 })
-                .map(|sq| ((sq.i, sq.j), sq))
+                .map(|sq| ((sq.i, sq.j), Arc::new(sq)))
                 .collect();
             GroupMapSquare { core_i, core_j, mapsquares }
         })
@@ -1,5 +1,5 @@
 use core::ops::RangeInclusive;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use itertools::iproduct;
 
@@ -9,8 +9,8 @@ use crate::{
         index::{self, CacheIndex},
     },
     definitions::{
-        indextype::IndexType,
-        mapsquares::{GroupMapSquare, MapSquare, MapSquares},
+        indextype::{IndexType, MapFileType},
+        mapsquares::{GroupMapSquare, MapSquare, MapSquareManifestEntry, MapSquares},
     },
 };
 impl MapSquares {
@@ -23,8 +23,24 @@ impl MapSquares {
     pub fn get(&self, i: u8, j: u8) -> CacheResult<MapSquare> {
         let archive_id = (i as u32) | (j as u32) << 7;
         let archive = self.index.archive(archive_id)?;
+        let version = self.index.metadatas().get(&archive_id).map(|metadata| metadata.version);
 
-        Ok(MapSquare::from_archive(archive))
+        Ok(MapSquare::from_archive(archive, version))
+    }
+
+    /// Lists every mapsquare in the cache. See [`MapSquareManifestEntry`].
+    pub fn manifest(&self) -> Vec<MapSquareManifestEntry> {
+        self.index
+            .metadatas()
+            .iter()
+            .map(|(archive_id, metadata)| MapSquareManifestEntry {
+                i: (archive_id & 0x7F) as u8,
+                j: (archive_id >> 7) as u8,
+                tiles: metadata.child_indices().contains(&MapFileType::TILES),
+                locations: metadata.child_indices().contains(&MapFileType::LOCATIONS),
+                archive_id: *archive_id,
+            })
+            .collect()
     }
 }
 /// Iterates over all [`MapSquare`]s in arbitrary order.
@@ -105,11 +121,13 @@ impl Iterator for GroupMapSquareIterator {
                 .filter(|(i, j)| *i >= 0 && *j >= 0)
                 .map(|(i, j)| (i + (j << 7)) as u32);
 
-            let archives = group_ids.filter_map(|archive_id| self.index.archive(archive_id).ok());
-
-            let mapsquares = archives
-                .map(MapSquare::from_archive)
-                .map(|sq| ((sq.i, sq.j), sq))
+            let mapsquares = group_ids
+                .filter_map(|archive_id| {
+                    let archive = self.index.archive(archive_id).ok()?;
+                    let version = self.index.metadatas().get(&archive_id).map(|metadata| metadata.version);
+                    Some(MapSquare::from_archive(archive, version))
+                })
+                .map(|sq| ((sq.i, sq.j), Arc::new(sq)))
                 .collect::<HashMap<_, _>>();
 
             GroupMapSquare { core_i, core_j, mapsquares }
@@ -1,8 +1,4 @@
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::BTreeMap, fs};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -698,10 +694,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     npc_configs.sort_unstable_by_key(|loc| loc.id);
     let path = path!(config.output / "npc_configs.json");
 
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-
-    let data = serde_json::to_string_pretty(&npc_configs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &npc_configs, config)?;
 
     Ok(())
 }
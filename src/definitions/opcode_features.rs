@@ -0,0 +1,69 @@
+use rs3cache_backend::buf::ReadError;
+
+/// The definitions whose opcodes depend on the shim features.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Definition {
+    Underlay,
+    Overlay,
+    LocationConfig,
+}
+
+/// Returns the feature that decodes `opcode` of `definition`, if that feature is not enabled.
+///
+/// Old school caches that use such an opcode are likely newer than the enabled features,
+/// so this is used to explain why an opcode could not be decoded.
+///
+/// Always returns `None` in rs3 and legacy builds, as their decoders do not depend on the shim features.
+pub fn required_feature(definition: Definition, opcode: u8) -> Option<&'static str> {
+    use Definition::*;
+
+    // The shim features build on each other, so enabling a feature also decodes the opcodes of all earlier ones.
+    let (feature, enabled) = match (definition, opcode) {
+        (Underlay, 2..=4) | (Overlay, 3 | 8 | 9 | 11..=14) | (LocationConfig, 16 | 82 | 90 | 96 | 102) => {
+            ("2008_3_shim", cfg!(feature = "2008_3_shim"))
+        }
+        (Overlay, 15) | (LocationConfig, 99 | 100 | 107) => ("2009_1_shim", cfg!(feature = "2009_1_shim")),
+        (Underlay, 5) | (Overlay, 16) => ("2010_1_shim", cfg!(feature = "2010_1_shim")),
+        (LocationConfig, 95 | 168 | 169) => ("2010_3_shim", cfg!(feature = "2010_3_shim")),
+        _ => return None,
+    };
+    (cfg!(feature = "osrs") && !enabled).then_some(feature)
+}
+
+/// The error for an `opcode` of `definition` that could not be decoded,
+/// naming the feature that decodes it if there is one, see [`required_feature`].
+#[track_caller]
+pub fn opcode_error(definition: Definition, opcode: u8) -> ReadError {
+    match required_feature(definition, opcode) {
+        Some(feature) => ReadError::opcode_needs_feature(opcode, feature),
+        None => ReadError::opcode_not_implemented(opcode),
+    }
+}
+
+#[cfg(test)]
+mod opcode_feature_tests {
+    use super::*;
+
+    #[test]
+    fn required_features() {
+        assert_eq!(required_feature(Definition::Underlay, 1), None);
+        assert_eq!(required_feature(Definition::Underlay, 255), None);
+
+        if cfg!(feature = "osrs") && !cfg!(feature = "2010_1_shim") {
+            assert_eq!(required_feature(Definition::Underlay, 5), Some("2010_1_shim"));
+        } else {
+            // This includes rs3 and legacy builds.
+            assert_eq!(required_feature(Definition::Underlay, 5), None);
+        }
+    }
+
+    #[test]
+    fn errors_name_the_feature() {
+        let error = opcode_error(Definition::Overlay, 16).to_string();
+        if cfg!(feature = "osrs") && !cfg!(feature = "2010_1_shim") {
+            assert!(error.contains("2010_1_shim"), "{error}");
+        } else {
+            assert!(!error.contains("shim"), "{error}");
+        }
+    }
+}
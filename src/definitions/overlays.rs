@@ -1,8 +1,4 @@
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::BTreeMap, fs};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -13,8 +9,15 @@ use serde::Serialize;
 use serde_with::skip_serializing_none;
 
 use crate::{
-    cache::{buf::BufExtra, error::CacheResult, index::CacheIndex},
-    definitions::indextype::{ConfigType, IndexType},
+    cache::{
+        buf::{BufExtra, ReadError},
+        error::CacheResult,
+        index::CacheIndex,
+    },
+    definitions::{
+        indextype::{ConfigType, IndexType},
+        opcode_features::{self, Definition},
+    },
 };
 /// Describes (part of) ground colour.
 #[cfg_attr(feature = "pyo3", pyclass(frozen))]
@@ -71,46 +74,52 @@ impl Overlay {
             .archive(ConfigType::OVERLAYS)?
             .take_files()
             .into_iter()
-            .map(|(file_id, file)| (file_id, Overlay::deserialize(file_id, file)))
-            .collect())
+            .map(|(file_id, file)| {
+                Overlay::deserialize(file_id, file)
+                    .map(|item| (file_id, item))
+                    .map_err(|e| e.add_context_id(file_id))
+            })
+            .collect::<Result<_, ReadError>>()?)
     }
 
-    fn deserialize(id: u32, mut buffer: Bytes) -> Overlay {
+    fn deserialize(id: u32, mut buffer: Bytes) -> Result<Overlay, ReadError> {
         let mut overlay = Overlay { id, ..Default::default() };
 
         loop {
-            let opcode = buffer.get_u8();
+            let opcode = buffer.try_get_u8()?;
             match opcode {
                 0 => {
-                    assert!(!buffer.has_remaining());
-                    break overlay;
+                    if buffer.has_remaining() {
+                        return Err(ReadError::not_exhausted());
+                    }
+                    break Ok(overlay);
                 }
-                1 => overlay.primary_colour = Some(buffer.get_rgb()),
+                1 => overlay.primary_colour = Some(buffer.try_get_rgb()?),
                 #[cfg(feature = "osrs")]
-                2 => overlay.texture = Some(buffer.get_u8()),
+                2 => overlay.texture = Some(buffer.try_get_u8()?),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                3 => overlay.op_3 = Some(buffer.get_u16()),
+                3 => overlay.op_3 = Some(buffer.try_get_u16()?),
                 5 => overlay.op_5 = Some(true),
-                7 => overlay.secondary_colour = Some(buffer.get_rgb()),
+                7 => overlay.secondary_colour = Some(buffer.try_get_rgb()?),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
                 8 => overlay.op_8 = Some(true),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                9 => overlay.op_9 = Some(buffer.get_u16()),
+                9 => overlay.op_9 = Some(buffer.try_get_u16()?),
                 #[cfg(feature = "rs3")]
                 10 => overlay.op_10 = Some(true),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                11 => overlay.op_11 = Some(buffer.get_u8()),
+                11 => overlay.op_11 = Some(buffer.try_get_u8()?),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
                 12 => overlay.op_12 = Some(true),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                13 => overlay.ternary_colour = Some(buffer.get_rgb()),
+                13 => overlay.ternary_colour = Some(buffer.try_get_rgb()?),
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                14 => overlay.op_14 = Some(buffer.get_u8()),
+                14 => overlay.op_14 = Some(buffer.try_get_u8()?),
                 #[cfg(feature = "2009_1_shim")]
-                15 => overlay.op_15 = Some(buffer.get_u16()),
+                15 => overlay.op_15 = Some(buffer.try_get_u16()?),
                 #[cfg(any(feature = "rs3", feature = "2010_1_shim"))]
-                16 => overlay.op_16 = Some(buffer.get_u8()),
-                missing => unimplemented!("Overlay::deserialize cannot deserialize opcode {} in id {}: {:?}", missing, id, buffer),
+                16 => overlay.op_16 = Some(buffer.try_get_u8()?),
+                missing => return Err(opcode_features::opcode_error(Definition::Overlay, missing)),
             }
         }
     }
@@ -131,8 +140,6 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     labels.sort_unstable_by_key(|loc| loc.id);
     let path = path!(&config.output / "overlays.json");
 
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&labels).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &labels, config)?;
     Ok(())
 }
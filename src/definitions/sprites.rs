@@ -13,7 +13,12 @@ use rs3cache_backend::{
     error::CacheError,
 };
 #[cfg(any(feature = "rs3", feature = "osrs"))]
-use {rayon::iter::ParallelIterator, rs3cache_utils::bar::Render};
+use {
+    crate::cache::arc::Archive,
+    rayon::iter::ParallelIterator,
+    rs3cache_utils::bar::Render,
+    std::path::{Path, PathBuf},
+};
 
 use crate::{
     cache::{error::CacheResult, index::CacheIndex},
@@ -21,13 +26,33 @@ use crate::{
 };
 
 /// Type alias for a rgba image.
+///
+/// Being an [`RgbaImage`], a sprite can be saved or drawn on directly.
 pub type Sprite = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
-/// Saves an image of every sprite to disk.
+/// Returns every frame of every sprite, keyed by their id and frame.
+///
+/// To save them to disk instead, see [`save_all`], which is exposed as `--dump sprites`.
+#[cfg(any(feature = "rs3", feature = "osrs"))]
+pub fn dump_all(config: &crate::cli::Config) -> CacheResult<BTreeMap<(u32, u32), Sprite>> {
+    let mut sprites = BTreeMap::new();
+    for archive in CacheIndex::new(IndexType::SPRITES, config.input.clone())? {
+        let archive = archive?;
+        let id = archive.archive_id();
+        for (frame, img) in deserialize(archive.file(&0)?)? {
+            sprites.insert((id, frame as u32), img);
+        }
+    }
+    Ok(sprites)
+}
+
+/// Saves every frame of every sprite as `sprites/{id}-{frame}.png`. Exposed as `--dump sprites`.
+///
+/// Sprites are decoded and saved one archive at a time.
 #[cfg(any(feature = "rs3", feature = "osrs"))]
 pub fn save_all(config: &crate::cli::Config) -> CacheResult<()> {
     let folder = path!(config.output / "sprites");
-    std::fs::create_dir_all(&folder).map_err(|e| CacheError::io(e, folder))?;
+    std::fs::create_dir_all(&folder).map_err(|e| CacheError::io(e, folder.clone()))?;
 
     let index = CacheIndex::new(IndexType::SPRITES, config.input.clone())?;
 
@@ -38,31 +63,42 @@ pub fn save_all(config: &crate::cli::Config) -> CacheResult<()> {
         .map(|(_, meta)| (meta.archive_id(), ::filetime::FileTime::from_unix_time(meta.version() as i64, 0)))
         .collect();
 
-    index.into_iter().render("sprites").for_each(|(archive, _)| {
-        let archive = archive.unwrap();
+    index.into_iter().render("sprites").try_for_each(|(archive, _)| {
+        let archive = archive?;
         debug_assert_eq!(archive.file_count(), 1);
 
-        let file = archive
-            .file(&0)
-            .unwrap_or_else(|error| panic!("Unable to get file for sprite {}: {} ", archive.archive_id(), error));
-        let images = deserialize(file).unwrap_or_else(|error| panic!("Error decoding sprite {}: {}", archive.archive_id(), error));
-        images.into_iter().for_each(|(frame, img)| {
-            let id = archive.archive_id();
-            let filename = path!(config.output / "sprites" / format!("{id}-{frame}.png"));
-            img.save(&filename)
-                .unwrap_or_else(|_| panic!("Unable to save sprite {}-{} to {}", id, frame, filename.to_string_lossy()));
-
-            #[cfg(feature = "rs3")]
-            {
-                let file = ::std::fs::OpenOptions::new().write(true).open(&filename).unwrap();
+        #[cfg(feature = "rs3")]
+        {
+            let date = versions[&archive.archive_id()];
+            for filename in save(archive, &folder)? {
+                let file = ::std::fs::OpenOptions::new()
+                    .write(true)
+                    .open(&filename)
+                    .map_err(|e| CacheError::io(e, filename.clone()))?;
+                ::filetime::set_file_handle_times(&file, Some(date), Some(date)).map_err(|e| CacheError::io(e, filename))?;
+            }
+        }
+        #[cfg(feature = "osrs")]
+        save(archive, &folder)?;
 
-                let date = versions[&id];
+        Ok(())
+    })
+}
 
-                ::filetime::set_file_handle_times(&file, Some(date), Some(date)).unwrap();
-            }
-        })
-    });
-    Ok(())
+/// Saves every frame of the sprite in `archive` as `{id}-{frame}.png` in `folder`, returning their filenames.
+#[cfg(any(feature = "rs3", feature = "osrs"))]
+fn save(archive: Archive, folder: &Path) -> CacheResult<Vec<PathBuf>> {
+    let id = archive.archive_id();
+    let mut filenames = Vec::new();
+    for (frame, img) in deserialize(archive.file(&0)?)? {
+        let filename = path!(folder / format!("{id}-{frame}.png"));
+        img.save(&filename).map_err(|e| match e {
+            image::ImageError::IoError(e) => CacheError::io(e, filename.clone()),
+            other => CacheError::image(other, filename.clone()),
+        })?;
+        filenames.push(filename);
+    }
+    Ok(filenames)
 }
 
 #[derive(Debug)]
@@ -373,6 +409,36 @@ mod sprite_tests {
 
         Ok(())
     }
+    #[test]
+    fn dump_all_matches_dumps() -> CacheResult<()> {
+        let config = crate::cli::Config::env();
+
+        let all = dump_all(&config)?;
+        let some = dumps(1, vec![0, 694], &config)?;
+        for (key, sprite) in some {
+            assert_eq!(all[&key], sprite, "{key:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn save_known_sprite() -> CacheResult<()> {
+        let config = crate::cli::Config::env();
+        let folder = std::env::temp_dir().join("rs3cache_sprites_test");
+        std::fs::create_dir_all(&folder).unwrap();
+
+        let archive = CacheIndex::new(IndexType::SPRITES, config.input)?.archive(694)?;
+        let frames = deserialize(archive.file(&0)?)?;
+        let filenames = save(archive, &folder)?;
+        assert_eq!(filenames.len(), frames.len());
+
+        let saved = image::open(path!(folder / "694-0.png")).unwrap().into_rgba8();
+        assert_eq!(saved, frames[&0]);
+
+        std::fs::remove_dir_all(folder).unwrap();
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn render_nonexistant() {
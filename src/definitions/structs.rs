@@ -1,10 +1,6 @@
 //! Describes the properties of structs.
 
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::BTreeMap, fs};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -96,9 +92,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
 
     let path = path!(&config.output / "structs.json");
 
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&structs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &structs, config)?;
 
     Ok(())
 }
@@ -1,16 +1,15 @@
 #![cfg(feature = "osrs")]
 
-use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::HashMap, fs};
 
-use bytes::{Buf, Bytes};
+use bytes::Bytes;
 use path_macro::path;
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
-use rs3cache_backend::error::CacheError;
+use rs3cache_backend::{
+    buf::{hsl16_to_rgb, BufExtra, ReadError},
+    error::CacheError,
+};
 use serde::Serialize;
 
 use crate::{
@@ -28,28 +27,38 @@ pub struct TextureConfig {
     /// Its id.
     pub id: u32,
     pub field1777: u16,
+    /// The average colour of the texture, decoded from `field1777`.
+    pub average_colour: [u8; 3],
     pub field1778: bool,
 }
 
 impl TextureConfig {
     /// Returns a mapping of all [`TextureConfig`]s.
     pub fn dump_all(config: &crate::cli::Config) -> CacheResult<HashMap<u32, Self>> {
-        let archives = CacheIndex::new(IndexType::TEXTURES, config.input.clone())?.into_iter();
-        let locations = archives
-            .flat_map(|archive| archive.unwrap().take_files().into_iter())
-            .map(|(id, file)| (id, Self::deserialize(id, file)))
-            .collect::<HashMap<u32, Self>>();
-        Ok(locations)
+        let mut textures = HashMap::new();
+        for archive in CacheIndex::new(IndexType::TEXTURES, config.input.clone())? {
+            for (id, file) in archive?.take_files() {
+                let texture = Self::deserialize(id, file).map_err(|e| e.add_context_id(id))?;
+                textures.insert(id, texture);
+            }
+        }
+        Ok(textures)
     }
 
-    fn deserialize(id: u32, mut buffer: Bytes) -> Self {
-        let field1777 = buffer.get_u16();
-        let field1778 = buffer.get_i8() != 0;
-        let _count = buffer.get_u8();
+    fn deserialize(id: u32, mut buffer: Bytes) -> Result<Self, ReadError> {
+        let field1777 = buffer.try_get_u16()?;
+        let average_colour = hsl16_to_rgb(field1777);
+        let field1778 = buffer.try_get_i8()? != 0;
+        let _count = buffer.try_get_u8()?;
 
         // todo: a lot of fields I dont care about
 
-        Self { id, field1777, field1778 }
+        Ok(Self {
+            id,
+            field1777,
+            average_colour,
+            field1778,
+        })
     }
 }
 
@@ -68,9 +77,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     let mut loc_configs = TextureConfig::dump_all(config)?.into_values().collect::<Vec<_>>();
     loc_configs.sort_unstable_by_key(|loc| loc.id);
     let path = path!(config.output / "textures.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&loc_configs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &loc_configs, config)?;
 
     Ok(())
 }
@@ -5,14 +5,16 @@ use ndarray::{Array, ArrayBase, Dim, OwnedRepr};
 use pyo3::prelude::*;
 #[allow(unused_imports)]
 use rs3cache_backend::buf::{BufExtra, ReadError};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::error::CacheResult;
 /// Type alias for the 4x64x64 array of [`Tile`]s in a [`MapSquare`](crate::definitions::mapsquares::MapSquare).
 pub type TileArray = ArrayBase<OwnedRepr<Tile>, Dim<[usize; 3]>>;
 
 /// Describes the properties of a tile in a [`MapSquare`](crate::definitions::mapsquares::MapSquare).
 
 #[cfg_attr(feature = "pyo3", pyclass(frozen, get_all))]
-#[derive(Default, Debug, Copy, Clone, Serialize)]
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct Tile {
     /// Reference to a [shape](crate::renderers::map::tileshape).
     pub shape: Option<u8>,
@@ -30,55 +32,148 @@ pub struct Tile {
     pub height: Option<u8>,
 }
 
+/// The shape of the overlay of a [`Tile`], before rotation.
+///
+/// The underlay fills the remainder of the tile.
+/// See [`tileshape`](crate::renderers::map::tileshape) for how these are drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum TileShape {
+    /// The overlay covers the entire tile.
+    Full,
+    /// The overlay covers one half of the tile, split along a diagonal.
+    Diagonal,
+    /// A narrow triangle along one edge of the tile.
+    NarrowWedge,
+    /// [`NarrowWedge`](TileShape::NarrowWedge), mirrored.
+    NarrowWedgeMirrored,
+    /// The complement of [`NarrowWedge`](TileShape::NarrowWedge).
+    WideWedge,
+    /// The complement of [`NarrowWedgeMirrored`](TileShape::NarrowWedgeMirrored).
+    WideWedgeMirrored,
+    /// The overlay covers one half of the tile, split along the middle.
+    Half,
+    /// A small triangle in one corner of the tile.
+    SmallCorner,
+    /// The complement of [`SmallCorner`](TileShape::SmallCorner).
+    LargeCorner,
+    /// Like [`Diagonal`](TileShape::Diagonal), turned a quarter further.
+    DiagonalTurned,
+    /// Like [`Diagonal`](TileShape::Diagonal), turned three quarters further.
+    DiagonalTurnedBack,
+    /// Like [`LargeCorner`](TileShape::LargeCorner), turned three quarters further.
+    LargeCornerTurnedBack,
+}
+
+impl TileShape {
+    /// Returns the shape, given the raw shape value of a [`Tile`].
+    ///
+    /// Returns `None` if the shape is not one of the twelve known shapes.
+    pub fn from_raw(raw: u8) -> Option<Self> {
+        let shape = match raw >> 2 {
+            0 => Self::Full,
+            1 => Self::Diagonal,
+            2 => Self::NarrowWedge,
+            3 => Self::NarrowWedgeMirrored,
+            4 => Self::WideWedge,
+            5 => Self::WideWedgeMirrored,
+            6 => Self::Half,
+            7 => Self::SmallCorner,
+            8 => Self::LargeCorner,
+            9 => Self::DiagonalTurned,
+            10 => Self::DiagonalTurnedBack,
+            11 => Self::LargeCornerTurnedBack,
+            _ => return None,
+        };
+        Some(shape)
+    }
+}
+
 impl Tile {
+    /// Reference to an [`Overlay`](crate::definitions::overlays::Overlay), if any.
+    pub fn overlay_id(&self) -> Option<u16> {
+        self.overlay_id
+    }
+
+    /// Reference to an [`Underlay`](crate::definitions::underlays::Underlay), if any.
+    pub fn underlay_id(&self) -> Option<u16> {
+        self.underlay_id
+    }
+
+    /// The [`TileShape`] of this tile's overlay. Tiles without a shape are [`Full`](TileShape::Full).
+    ///
+    /// Returns `None` if the shape is not one of the twelve known shapes.
+    pub fn shape(&self) -> Option<TileShape> {
+        TileShape::from_raw(self.shape.unwrap_or(0))
+    }
+
+    /// The number of quarter turns applied to this tile's [`shape`](Tile::shape), in the range `0..=3`.
+    pub fn rotation(&self) -> u8 {
+        self.shape.unwrap_or(0) & 0x3
+    }
+
+    /// The height of the tile, if any.
+    pub fn height(&self) -> Option<u8> {
+        self.height
+    }
+
     /// Constructor for a sequence of [`Tile`]s.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ReadError`] if the buffer is truncated.
     #[cfg(any(feature = "rs3", feature = "2013_shim"))]
-    pub fn dump(buffer: &mut Bytes) -> TileArray {
-        Array::from_shape_simple_fn((4, 64, 64), || {
+    pub fn dump(buffer: &mut Bytes) -> CacheResult<TileArray> {
+        let producer = || try {
             let mut tile = Tile::default();
 
-            let [flag_1, flag_2, flag_3, flag_4, ..] = buffer.get_bitflags();
+            let [flag_1, flag_2, flag_3, flag_4, ..] = buffer.try_get_bitflags()?;
 
             if flag_1 {
-                tile.shape = Some(buffer.get_u8());
-                tile.overlay_id = Some(buffer.get_unsigned_smart());
+                tile.shape = Some(buffer.try_get_u8()?);
+                tile.overlay_id = Some(buffer.try_get_unsigned_smart()?);
             }
 
             if flag_2 {
-                tile.settings = Some(buffer.get_u8());
+                tile.settings = Some(buffer.try_get_u8()?);
             }
 
             if flag_3 {
-                tile.underlay_id = Some(buffer.get_unsigned_smart());
+                tile.underlay_id = Some(buffer.try_get_unsigned_smart()?);
             }
 
             if flag_4 {
-                tile.height = Some(buffer.get_u8());
+                tile.height = Some(buffer.try_get_u8()?);
             }
 
             tile
-        })
+        };
+
+        let shape = std::iter::repeat_with(producer)
+            .take(4 * 64 * 64)
+            .collect::<Result<Vec<Tile>, ReadError>>()?;
+
+        Ok(Array::from_shape_vec((4, 64, 64), shape).unwrap())
     }
 
     #[cfg(feature = "legacy")]
-    pub fn dump(buffer: &mut Bytes) -> TileArray {
-        let shape = Self::try_dump(buffer.clone(), false).unwrap();
+    pub fn dump(buffer: &mut Bytes) -> CacheResult<TileArray> {
+        let shape = Self::try_dump(buffer.clone(), false)?;
 
-        Array::from_shape_vec((4, 64, 64), shape).unwrap()
+        Ok(Array::from_shape_vec((4, 64, 64), shape).unwrap())
     }
 
     #[cfg(all(feature = "osrs", not(feature = "2013_shim")))]
-    pub fn dump(buffer: &mut Bytes) -> TileArray {
+    pub fn dump(buffer: &mut Bytes) -> CacheResult<TileArray> {
         // This is a hack to deal with the changing of the tile format
         //
         // Rather than introducing a new feature for it,
         // try to figure out the correct format at runtime
         let shape = match Self::try_dump(buffer.clone(), true) {
             Ok(shape) => shape,
-            Err(_) => Self::try_dump(buffer.clone(), false).unwrap(),
+            Err(_) => Self::try_dump(buffer.clone(), false)?,
         };
 
-        Array::from_shape_vec((4, 64, 64), shape).unwrap()
+        Ok(Array::from_shape_vec((4, 64, 64), shape).unwrap())
     }
 
     #[cfg(any(feature = "osrs", feature = "legacy"))]
@@ -123,3 +218,57 @@ impl Tile {
         }
     }
 }
+
+#[cfg(test)]
+mod tile_tests {
+    use super::*;
+
+    #[test]
+    fn shape_and_rotation() {
+        let tile = Tile {
+            shape: Some(4 * 7 + 2),
+            ..Default::default()
+        };
+        assert_eq!(tile.shape(), Some(TileShape::SmallCorner));
+        assert_eq!(tile.rotation(), 2);
+
+        let tile = Tile::default();
+        assert_eq!(tile.shape(), Some(TileShape::Full));
+        assert_eq!(tile.rotation(), 0);
+    }
+
+    #[test]
+    fn all_shapes() {
+        for raw in 0..48 {
+            assert!(TileShape::from_raw(raw).is_some(), "{raw}");
+        }
+        for raw in 48..=255 {
+            assert_eq!(TileShape::from_raw(raw), None, "{raw}");
+        }
+
+        let tile = Tile {
+            shape: Some(48),
+            ..Default::default()
+        };
+        assert_eq!(tile.shape(), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let tile = Tile {
+            shape: Some(6),
+            overlay_id: Some(40),
+            height: Some(12),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(tile).unwrap();
+        let parsed: Tile = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), json);
+    }
+
+    #[test]
+    fn truncated() {
+        let err = Tile::dump(&mut Bytes::from_static(&[0x1])).unwrap_err();
+        assert!(matches!(err.kind(), crate::cache::error::CacheErrorKind::ReadError(_)));
+    }
+}
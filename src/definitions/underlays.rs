@@ -1,26 +1,29 @@
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::BTreeMap, fs};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
 #[cfg(feature = "pyo3")]
 use pyo3::prelude::*;
 use rs3cache_backend::error::CacheError;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
 use crate::{
-    cache::{buf::BufExtra, error::CacheResult, index::CacheIndex},
-    definitions::indextype::{ConfigType, IndexType},
+    cache::{
+        buf::{BufExtra, ReadError},
+        error::CacheResult,
+        index::CacheIndex,
+    },
+    definitions::{
+        indextype::{ConfigType, IndexType},
+        opcode_features::{self, Definition},
+    },
 };
 
 /// Describes the general ground colour. This colour is blended with surrounding tiles.
 #[cfg_attr(feature = "pyo3", pyclass(frozen))]
 #[skip_serializing_none]
-#[derive(Debug, Default, Copy, Clone, Serialize)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 
 pub struct Underlay {
     /// Id of the underlay configuration.
@@ -42,33 +45,61 @@ impl Underlay {
     pub fn dump_all(config: &crate::cli::Config) -> CacheResult<BTreeMap<u32, Underlay>> {
         Ok(CacheIndex::new(IndexType::CONFIG, config.input.clone())?
             .archive(ConfigType::UNDERLAYS)?
-            .take_files()
-            .into_iter()
-            .map(|(file_id, file)| (file_id, Underlay::deserialize(file_id, file)))
-            .collect())
+            .files_iter()
+            .map(|(file_id, file)| {
+                Underlay::deserialize(file_id, file)
+                    .map(|item| (file_id, item))
+                    .map_err(|e| e.add_context_id(file_id))
+            })
+            .collect::<Result<_, ReadError>>()?)
+    }
+
+    /// The id of the texture drawn over this underlay, if any.
+    #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
+    pub fn texture(&self) -> Option<u16> {
+        self.op_2.filter(|&texture| texture != u16::MAX)
+    }
+
+    /// The size of the [`texture`](Underlay::texture), in quarter tiles.
+    #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
+    pub fn texture_size(&self) -> Option<u16> {
+        self.op_3
+    }
+
+    /// Whether this underlay is shadowed by locations standing on it.
+    ///
+    /// This is the default; underlays with opcode 4 opt out of it.
+    #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
+    pub fn blocks_shadow(&self) -> bool {
+        self.op_4.is_none()
     }
 
-    fn deserialize(id: u32, mut buffer: Bytes) -> Underlay {
+    fn deserialize(id: u32, mut buffer: Bytes) -> Result<Underlay, ReadError> {
         let mut underlay = Underlay { id, ..Default::default() };
 
         loop {
-            let opcode = buffer.get_u8();
+            let opcode = buffer.try_get_u8()?;
             match opcode {
                 0 => {
-                    assert!(!buffer.has_remaining());
-                    break underlay;
+                    if buffer.has_remaining() {
+                        return Err(ReadError::not_exhausted());
+                    }
+                    break Ok(underlay);
                 }
-                1 => underlay.colour = Some(buffer.get_rgb()),
+                1 => underlay.colour = Some(buffer.try_get_rgb()?),
+                // The texture id, see `Underlay::texture`. This is not a second colour.
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                2 => underlay.op_2 = Some(buffer.get_u16()),
+                2 => underlay.op_2 = Some(buffer.try_get_u16()?),
+                // The texture size, see `Underlay::texture_size`.
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
-                3 => underlay.op_3 = Some(buffer.get_u16()),
+                3 => underlay.op_3 = Some(buffer.try_get_u16()?),
+                // Opts out of being shadowed, see `Underlay::blocks_shadow`.
                 #[cfg(any(feature = "rs3", feature = "2008_3_shim"))]
                 4 => underlay.op_4 = Some(true),
                 #[cfg(any(feature = "rs3", feature = "2010_1_shim"))]
                 5 => underlay.op_5 = Some(true),
 
-                missing => unimplemented!("Underlay::deserialize cannot deserialize opcode {} in id {}", missing, id),
+                missing => return Err(opcode_features::opcode_error(Definition::Underlay, missing)),
             }
         }
     }
@@ -80,10 +111,35 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     let mut underlay = Underlay::dump_all(config)?.into_values().collect::<Vec<_>>();
     underlay.sort_unstable_by_key(|loc| loc.id);
     let path = path!(config.output / "underlays.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-
-    let data = serde_json::to_string_pretty(&underlay).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &underlay, config)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod underlay_tests {
+    use super::*;
+
+    #[test]
+    // Old school underlays have no fields besides these.
+    #[cfg_attr(not(feature = "rs3"), allow(clippy::needless_update))]
+    fn round_trip() {
+        let underlay = Underlay {
+            id: 3,
+            colour: Some([10, 20, 30]),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(underlay).unwrap();
+        let parsed: Underlay = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(parsed).unwrap(), json);
+    }
+
+    #[test]
+    fn truncated() {
+        let underlay = Underlay::deserialize(3, Bytes::from_static(&[1, 10, 20, 30, 0])).unwrap();
+        assert_eq!(underlay.colour, Some([10, 20, 30]));
+
+        assert!(Underlay::deserialize(3, Bytes::from_static(&[1, 10, 20])).is_err());
+        assert!(Underlay::deserialize(3, Bytes::new()).is_err());
+    }
+}
@@ -3,11 +3,7 @@
 //!
 //! See also [`Varp`](crate::types::variables::Varp) and [`Varbit`](crate::types::variables::Varbit).
 
-use std::{
-    collections::BTreeMap,
-    fs::{self, File},
-    io::Write,
-};
+use std::{collections::BTreeMap, fs};
 
 use bytes::{Buf, Bytes};
 use path_macro::path;
@@ -88,10 +84,7 @@ pub fn export(config: &crate::cli::Config) -> CacheResult<()> {
     vb_configs.sort_unstable_by_key(|loc| loc.id);
 
     let path = path!(config.output / "varbit_configs.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-
-    let data = serde_json::to_string_pretty(&vb_configs).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &vb_configs, config)?;
 
     Ok(())
 }
@@ -313,9 +313,7 @@ pub fn export_pastes(config: &crate::cli::Config) -> CacheResult<()> {
     let map_pastes: BTreeMap<u32, MapPastes> = MapPastes::dump_all(config)?.into_iter().collect();
 
     let path = path!(config.output / "map_pastes.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&map_pastes).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &map_pastes, config)?;
     Ok(())
 }
 
@@ -326,9 +324,7 @@ pub fn export_zones(config: &crate::cli::Config) -> CacheResult<()> {
     let mut map_zones = MapZone::dump_all(config)?.into_values().collect::<Vec<_>>();
     map_zones.sort_unstable_by_key(|loc| loc.id);
     let path = path!(config.output / "map_zones.json");
-    let mut file = File::create(&path).map_err(|e| CacheError::io(e, path.clone()))?;
-    let data = serde_json::to_string_pretty(&map_zones).unwrap();
-    file.write_all(data.as_bytes()).map_err(|e| CacheError::io(e, path))?;
+    crate::cli::write_serialized(path, &map_zones, config)?;
     Ok(())
 }
 
@@ -20,6 +20,8 @@
 //! get_varbit_configs()
 //! get_struct_configs()
 //! get_enum_configs()
+//! locations_of_square(i, j)
+//! tiles_of_square(i, j)
 //! ```
 //! ## Classes
 //!
@@ -48,8 +50,8 @@ use crate::definitions::achievements::Achievement;
 use crate::{
     cli::Config,
     definitions::{
-        enums::Enum, item_configs::ItemConfig, location_configs::LocationConfig, npc_configs::NpcConfig, structs::Struct,
-        varbit_configs::VarbitConfig,
+        enums::Enum, item_configs::ItemConfig, location_configs::LocationConfig, locations::Location, mapsquares::MapSquares, npc_configs::NpcConfig,
+        structs::Struct, tiles::Tile, varbit_configs::VarbitConfig,
     },
 };
 
@@ -62,6 +64,8 @@ pub fn initializer(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(get_varbit_configs, m)?)?;
     m.add_function(wrap_pyfunction!(get_struct_configs, m)?)?;
     m.add_function(wrap_pyfunction!(get_enum_configs, m)?)?;
+    m.add_function(wrap_pyfunction!(locations_of_square, m)?)?;
+    m.add_function(wrap_pyfunction!(tiles_of_square, m)?)?;
     m.add_function(wrap_pyfunction!(hash_djb2, m)?)?;
 
     m.add_class::<PyMapSquares>()?;
@@ -84,7 +88,7 @@ pub fn initializer(py: Python, m: &PyModule) -> PyResult<()> {
 pub fn get_achievement_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, Achievement>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(Achievement::dump_all(&config)?)
 }
@@ -94,7 +98,7 @@ pub fn get_achievement_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32,
 pub fn get_location_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, LocationConfig>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(LocationConfig::dump_all(&config)?)
 }
@@ -104,7 +108,7 @@ pub fn get_location_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, Loc
 pub fn get_npc_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, NpcConfig>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(NpcConfig::dump_all(&config)?)
 }
@@ -114,7 +118,7 @@ pub fn get_npc_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, NpcConfi
 pub fn get_item_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, ItemConfig>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(ItemConfig::dump_all(&config)?)
 }
@@ -124,7 +128,7 @@ pub fn get_item_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, ItemCon
 pub fn get_struct_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, Struct>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(Struct::dump_all(&config)?)
 }
@@ -134,7 +138,7 @@ pub fn get_struct_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, Struc
 pub fn get_enum_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, Enum>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(Enum::dump_all(&config)?)
 }
@@ -144,11 +148,33 @@ pub fn get_enum_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, Enum>>
 pub fn get_varbit_configs(path: Option<PathBuf>) -> PyResult<BTreeMap<u32, VarbitConfig>> {
     let mut config = Config::env();
     if let Some(path) = path {
-        config.input = Arc::new(CachePath::Given(path))
+        config.input = Arc::new(CachePath::given(path))
     }
     Ok(VarbitConfig::dump_all(&config)?)
 }
 
+/// Wrapper for [`MapSquare::take_locations`](crate::definitions::mapsquares::MapSquare::take_locations)
+#[pyfunction]
+pub fn locations_of_square(i: u8, j: u8, path: Option<PathBuf>) -> PyResult<Vec<Location>> {
+    let mut config = Config::env();
+    if let Some(path) = path {
+        config.input = Arc::new(CachePath::given(path))
+    }
+    Ok(MapSquares::new(&config)?.get(i, j)?.take_locations()?)
+}
+
+/// Wrapper for [`MapSquare::take_tiles`](crate::definitions::mapsquares::MapSquare::take_tiles),
+/// keyed by `(plane, x, y)`.
+#[pyfunction]
+pub fn tiles_of_square(i: u8, j: u8, path: Option<PathBuf>) -> PyResult<BTreeMap<(u8, u8, u8), Tile>> {
+    let mut config = Config::env();
+    if let Some(path) = path {
+        config.input = Arc::new(CachePath::given(path))
+    }
+    let tiles = MapSquares::new(&config)?.get(i, j)?.take_tiles()?;
+    Ok(tiles.indexed_iter().map(|((p, x, y), &t)| ((p as u8, x as u8, y as u8), t)).collect())
+}
+
 #[pyfunction]
 pub fn hash_djb2(s: &str) -> i32 {
     rs3cache_backend::hash::hash_djb2(s)
@@ -44,7 +44,7 @@ impl PyCacheIndex {
     fn new(index_id: u32, path: Option<PathBuf>) -> PyResult<Self> {
         let mut config = Config::env();
         if let Some(path) = path {
-            config.input = Arc::new(CachePath::Given(path))
+            config.input = Arc::new(CachePath::given(path))
         }
 
         Ok(Self {
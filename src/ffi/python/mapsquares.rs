@@ -35,7 +35,7 @@ impl PyMapSquares {
     fn new(path: Option<PathBuf>) -> PyResult<Self> {
         let mut config = Config::env();
         if let Some(path) = path {
-            config.input = Arc::new(CachePath::Given(path))
+            config.input = Arc::new(CachePath::given(path))
         }
         Ok(Self {
             mapsquares: Some(MapSquares::new(&config)?),
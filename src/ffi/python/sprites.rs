@@ -76,7 +76,7 @@ impl PySprites {
     fn __new__(py: Python, path: Option<PathBuf>) -> PyResult<Self> {
         let mut config = Config::env();
         if let Some(path) = path {
-            config.input = Arc::new(CachePath::Given(path))
+            config.input = Arc::new(CachePath::given(path))
         }
         let constructor = py.import("PIL")?.getattr("Image")?.getattr("frombytes")?.into();
 
@@ -1,126 +1,125 @@
 use std::{
-    collections::BTreeSet,
-    sync::Mutex,
+    fs,
     time::{Duration, Instant},
 };
 
-use clap::{ArgEnum, Parser};
-use console::style;
-use indicatif::{ProgressBar, ProgressStyle};
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use clap::Parser;
+use path_macro::path;
+use rs3cache_backend::error::{CacheError, CacheResult};
 
-use crate::cli::{Config, Dump};
+use crate::cli::{Config, Dump, DumpSet};
 
 /// Entry point for the program. Run the executable with `--help` for a list of commands.
 pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::parse();
 
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
     let start = Instant::now();
 
+    #[cfg(feature = "rs3")]
+    let config = {
+        let mut config = config;
+        if let Some(timeout) = config.busy_timeout {
+            config.input = std::sync::Arc::new((*config.input).clone().with_busy_timeout(Duration::from_millis(timeout)));
+        }
+        config
+    };
+
     #[cfg(all(feature = "rs3", not(feature = "mockdata"), not(feature = "save_mockdata")))]
     if config.assert_coherence {
         crate::cache::index::assert_coherence(config.input.clone())?;
     }
 
-    {
-        let mut to_dump = config.dump.clone();
-
-        if config.dump.contains(&Dump::Configs) {
-            to_dump.extend_from_slice(Dump::configs())
+    if let Some(&[index_id, archive_id, file_id]) = config.dump_file.as_deref() {
+        let (file, name) = if config.raw {
+            (
+                crate::cli::dump_archive_raw(&config, index_id, archive_id)?,
+                format!("{index_id}_{archive_id}_raw.bin"),
+            )
+        } else {
+            (
+                crate::cli::dump_file(&config, index_id, archive_id, file_id)?,
+                format!("{index_id}_{archive_id}_{file_id}.bin"),
+            )
+        };
+        if config.write_file {
+            fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.to_path_buf()))?;
+            let path = path!(config.output / name);
+            fs::write(&path, &file).map_err(|e| CacheError::io(e, path))?;
+        } else {
+            print!("{}", crate::cli::hexdump(&file));
         }
+    }
 
-        if config.dump.contains(&Dump::All) {
-            to_dump.extend_from_slice(Dump::value_variants())
-        }
-
-        let dump_sprites = to_dump.contains(&Dump::Sprites);
-
-        #[cfg(feature = "rs3")]
-        let dump_music = to_dump.contains(&Dump::Music);
-
-        #[cfg(feature = "rs3")]
-        let has_bars = [Dump::All, Dump::Configs, Dump::Sprites, Dump::Music];
-
-        #[cfg(feature = "osrs")]
-        let has_bars = [Dump::All, Dump::Configs, Dump::Sprites];
-
-        #[cfg(feature = "legacy")]
-        let has_bars = [];
+    if let Some(&[plane, x, y]) = config.inspect_tile.as_deref() {
+        let inspection = crate::definitions::mapsquares::inspect_tile(&config, x, y, plane as u8)?;
+        println!("{}", serde_json::to_string_pretty(&inspection)?);
+    }
 
-        to_dump.retain(|item| !has_bars.contains(item));
+    #[cfg(any(feature = "rs3", feature = "osrs"))]
+    if config.fingerprint {
+        let fingerprint = rs3cache_backend::index::cache_fingerprint(config.input.clone())?;
+        println!("{}", serde_json::to_string_pretty(&fingerprint)?);
+    }
 
-        // Remove duplicates.
-        let to_dump = to_dump.into_iter().collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>();
+    match config.threads {
+        Some(threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()?
+            .install(|| export(&config))?,
+        None => export(&config)?,
+    }
 
-        let progress = ProgressBar::new(to_dump.len() as u64).with_style(
-            ProgressStyle::with_template(&format!(
-                "   {} [{{bar:30}}] {{pos}}/{{len}}: {{wide_msg}}",
-                style("Dumping").cyan().bright()
-            ))
-            .unwrap()
-            .progress_chars("=> "),
-        );
+    let dt = start.elapsed();
 
-        let messages = Mutex::new(Vec::new());
+    if dt > Duration::from_secs(1) {
+        println!("\nFinished program in {} s.", dt.as_secs());
+    } else {
+        println!("\nFinished program in {} ms.", dt.as_millis());
+    }
 
-        to_dump
-            .into_par_iter()
-            .map(|a| {
-                let name = a.as_str();
-                {
-                    let mut messages = messages.lock().unwrap();
-                    messages.push(name);
-                    let msg = messages.join(", ");
+    Ok(())
+}
 
-                    progress.set_message(msg);
+/// Runs the exporters and renderers requested by `config`.
+fn export(config: &Config) -> CacheResult<()> {
+    let to_dump: DumpSet = config.dump.iter().cloned().collect();
 
-                    drop(messages);
-                };
+    let dump_sprites = to_dump.contains(&Dump::Sprites);
 
-                let ret = a.call()(&config);
-                progress.inc(1);
-                {
-                    let mut messages = messages.lock().unwrap();
+    #[cfg(feature = "rs3")]
+    let dump_music = to_dump.contains(&Dump::Music);
 
-                    messages.retain(|&n| n != name);
-                    let msg = messages.join(", ");
-                    progress.set_message(msg);
+    #[cfg(feature = "rs3")]
+    let has_bars = [Dump::Sprites, Dump::Music];
 
-                    let news = if ret.is_err() {
-                        format!("    {} failed dumping {a}", style("Error").red())
-                    } else {
-                        format!("    {} {name}", style("Dumped").green().bright())
-                    };
-                    progress.println(news);
-                    drop(messages);
-                };
+    #[cfg(feature = "osrs")]
+    let has_bars = [Dump::Sprites];
 
-                ret
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        progress.finish_and_clear();
+    #[cfg(feature = "legacy")]
+    let has_bars = [];
 
-        if dump_sprites {
-            Dump::Sprites.call()(&config)?;
-        }
+    let to_dump: DumpSet = to_dump.into_iter().filter(|item| !has_bars.contains(item)).collect();
 
-        #[cfg(feature = "rs3")]
-        if dump_music {
-            Dump::Music.call()(&config)?;
-        }
+    if !to_dump.is_empty() {
+        let summary = crate::cli::dump_all(config, to_dump)?;
+        print!("{summary}");
+        summary.into_result()?;
+    }
 
-        #[cfg(not(target_arch = "wasm32"))]
-        for map in &(config.render) {
-            map.call(&config)?;
-        }
+    if dump_sprites {
+        Dump::Sprites.call()(config)?;
     }
 
-    let dt = start.elapsed();
+    #[cfg(feature = "rs3")]
+    if dump_music {
+        Dump::Music.call()(config)?;
+    }
 
-    if dt > Duration::from_secs(1) {
-        println!("\nFinished program in {} s.", dt.as_secs());
-    } else {
-        println!("\nFinished program in {} ms.", dt.as_millis());
+    #[cfg(not(target_arch = "wasm32"))]
+    for map in &(config.render) {
+        map.call(config)?;
     }
 
     Ok(())
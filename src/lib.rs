@@ -50,6 +50,9 @@ pub mod definitions {
 
     pub mod dbrows;
 
+    /// Configuration of database tables.
+    pub mod dbtables;
+
     #[cfg(feature = "legacy")]
     pub mod flo;
 
@@ -69,6 +72,9 @@ pub mod definitions {
     /// Describes the position and id of npcs.
     pub mod npcs;
 
+    /// Which opcodes need which features, to explain why an opcode could not be decoded.
+    pub mod opcode_features;
+
     pub mod maplabel_configs;
 
     /// Configuration of images drawn on the world map.
@@ -107,9 +113,19 @@ pub mod definitions {
 /// Functions for rendering the map.
 #[cfg(all(not(target_arch = "wasm32"), any(feature = "rs3", feature = "osrs", feature = "legacy")))]
 pub mod renderers {
+    /// Selects the image format of rendered tiles.
+    pub mod encode;
+
+    /// Renders shaded relief of the terrain.
+    pub mod hillshade;
+
     /// Exports map tiles.
     pub mod map;
 
+    /// Renders the colours of all underlays and overlays.
+    #[cfg(any(feature = "rs3", feature = "osrs"))]
+    pub mod palette;
+
     pub mod scale;
 
     /// Creates successive tiles for different zoom levels,
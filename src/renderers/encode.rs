@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+#[cfg(feature = "webp")]
+use std::{fs::File, io::BufWriter};
+
+use clap::ArgEnum;
+#[cfg(feature = "webp")]
+use image::codecs::webp::{WebPEncoder, WebPQuality};
+use image::{ImageError, RgbaImage};
+use rs3cache_backend::error::CacheError;
+
+use crate::{cache::error::CacheResult, cli::Config};
+
+/// The image format in which rendered tiles are saved.
+#[derive(ArgEnum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[clap(rename_all = "snake_case")]
+pub enum ImageFormat {
+    /// Lossless png.
+    #[default]
+    Png,
+    /// Uncompressed bitmap.
+    Bmp,
+    /// Lossy webp, with the quality given by `--image-quality`.
+    #[cfg(feature = "webp")]
+    Webp,
+    /// Lossless webp.
+    #[cfg(feature = "webp")]
+    WebpLossless,
+}
+
+impl ImageFormat {
+    /// The file extension of this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Bmp => "bmp",
+            #[cfg(feature = "webp")]
+            ImageFormat::Webp | ImageFormat::WebpLossless => "webp",
+        }
+    }
+}
+
+/// Encodes `img` in the [`ImageFormat`] given by `config`, and saves it to `path`.
+///
+/// The extension of `path` is replaced by the [extension](ImageFormat::extension) of that format.
+pub fn save(img: &RgbaImage, path: PathBuf, config: &Config) -> CacheResult<()> {
+    let path = path.with_extension(config.image_format.extension());
+
+    let result = match config.image_format {
+        ImageFormat::Png => img.save_with_format(&path, image::ImageFormat::Png),
+        ImageFormat::Bmp => img.save_with_format(&path, image::ImageFormat::Bmp),
+        #[cfg(feature = "webp")]
+        format @ (ImageFormat::Webp | ImageFormat::WebpLossless) => {
+            let quality = if format == ImageFormat::WebpLossless {
+                WebPQuality::lossless()
+            } else {
+                WebPQuality::lossy(config.image_quality)
+            };
+            File::create(&path).map_err(ImageError::IoError).and_then(|file| {
+                WebPEncoder::new_with_quality(BufWriter::new(file), quality).encode(img.as_raw(), img.width(), img.height(), image::ColorType::Rgba8)
+            })
+        }
+    };
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(ImageError::IoError(e)) => Err(CacheError::io(e, path)),
+        Err(other) => Err(CacheError::image(other, path)),
+    }
+}
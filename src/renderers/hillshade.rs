@@ -0,0 +1,139 @@
+use std::fs;
+
+use image::{Rgba, RgbaImage};
+use path_macro::path;
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use rs3cache_backend::error::CacheError;
+
+use crate::{
+    cache::error::CacheResult,
+    cli::Config,
+    definitions::mapsquares::{GroupMapSquare, GroupMapSquareIterator},
+    renderers::encode,
+};
+
+/// The height of one unit of [`Tile::height`](crate::definitions::tiles::Tile::height), in tiles.
+///
+/// Heights are stored in multiples of 8, and a tile is 128 units wide.
+const HEIGHT_SCALE: f32 = 8.0 / 128.0;
+
+/// The light source of a [`hillshade`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Light {
+    /// The direction the light comes from, in degrees clockwise from north.
+    pub azimuth: f32,
+    /// The angle of the light above the horizon, in degrees.
+    pub altitude: f32,
+    /// The factor by which heights are multiplied, to make small differences visible.
+    pub exaggeration: f32,
+}
+
+impl Default for Light {
+    /// Light from the north west, the usual direction for shaded relief.
+    fn default() -> Self {
+        Self {
+            azimuth: 315.0,
+            altitude: 45.0,
+            exaggeration: 1.0,
+        }
+    }
+}
+
+impl Light {
+    /// The light given by `--hillshade-azimuth`, `--hillshade-altitude` and `--hillshade-exaggeration`.
+    pub fn from_config(config: &Config) -> Self {
+        let default = Self::default();
+        Self {
+            azimuth: config.hillshade_azimuth.unwrap_or(default.azimuth),
+            altitude: config.hillshade_altitude.unwrap_or(default.altitude),
+            exaggeration: config.hillshade_exaggeration.unwrap_or(default.exaggeration),
+        }
+    }
+
+    /// The brightness of a surface whose height increases by `dx` and `dy` tiles per tile eastward and northward.
+    fn shade(&self, dx: f32, dy: f32) -> u8 {
+        let (azimuth, altitude) = (self.azimuth.to_radians(), self.altitude.to_radians());
+        let light = [altitude.cos() * azimuth.sin(), altitude.cos() * azimuth.cos(), altitude.sin()];
+
+        let (dx, dy) = (dx * self.exaggeration, dy * self.exaggeration);
+        let length = (dx * dx + dy * dy + 1.0).sqrt();
+        let normal = [-dx / length, -dy / length, 1.0 / length];
+
+        let brightness = normal.iter().zip(light).map(|(n, l)| n * l).sum::<f32>();
+        (brightness.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+}
+
+/// Renders shaded relief of the ground of every [`MapSquare`](crate::definitions::mapsquares::MapSquare), lit by [`Light::from_config`].
+///
+/// Every tile is one pixel, and images are saved as `hillshade/{i}_{j}` with the extension of `--image-format`.
+/// Tiles on the edge of a square use the heights of neighbouring squares, so the shading is continuous across squares.
+pub fn hillshade(config: &Config) -> CacheResult<()> {
+    let folder = path!(config.output / "hillshade");
+    fs::create_dir_all(&folder).map_err(|e| CacheError::io(e, folder.clone()))?;
+
+    let light = Light::from_config(config);
+
+    GroupMapSquareIterator::new(-1..=1, -1..=1, config)?
+        .par_bridge()
+        .try_for_each(|squares| match render_square(&squares, 0, light) {
+            Some(img) => encode::save(&img, path!(folder / format!("{}_{}.png", squares.core_i(), squares.core_j())), config),
+            None => Ok(()),
+        })
+}
+
+/// Renders the shaded relief of `plane` of the central square of `squares`, or `None` if it has no tiles.
+pub fn render_square(squares: &GroupMapSquare, plane: usize, light: Light) -> Option<RgbaImage> {
+    let tiles = squares.core()?.tiles().ok()?;
+
+    // The height of the tile at `x, y`, which may lie in a neighbouring square.
+    // Missing tiles take the height of the tile at `fallback`, so they do not cause slopes.
+    let height = |x: isize, y: isize, fallback: (usize, usize)| -> f32 {
+        let i = squares.core_i() as isize + x.div_euclid(64);
+        let j = squares.core_j() as isize + y.div_euclid(64);
+        let tile = u8::try_from(i)
+            .ok()
+            .zip(u8::try_from(j).ok())
+            .and_then(|key| squares.get(&key)?.tiles().ok())
+            .and_then(|tiles| tiles.get([plane, x.rem_euclid(64) as usize, y.rem_euclid(64) as usize]))
+            .unwrap_or(&tiles[[plane, fallback.0, fallback.1]]);
+        tile.height.unwrap_or(0) as f32 * HEIGHT_SCALE
+    };
+
+    let mut img = RgbaImage::new(64, 64);
+    for (x, y) in itertools::iproduct!(0..64_usize, 0..64_usize) {
+        let (xx, yy) = (x as isize, y as isize);
+        let dx = (height(xx + 1, yy, (x, y)) - height(xx - 1, yy, (x, y))) / 2.0;
+        let dy = (height(xx, yy + 1, (x, y)) - height(xx, yy - 1, (x, y))) / 2.0;
+        let shade = light.shade(dx, dy);
+
+        // Images have their origin in the top left, but `y` increases northward.
+        img.put_pixel(x as u32, 63 - y as u32, Rgba([shade, shade, shade, 255]));
+    }
+    Some(img)
+}
+
+#[cfg(test)]
+mod hillshade_tests {
+    use super::*;
+
+    #[test]
+    fn flat_ground() {
+        let light = Light::default();
+        assert_eq!(light.shade(0.0, 0.0), (45_f32.to_radians().sin() * 255.0).round() as u8);
+    }
+
+    #[test]
+    fn slopes() {
+        let light = Light::default();
+
+        // Ground that rises to the east faces towards light coming from the north west.
+        let towards = light.shade(1.0, 0.0);
+        let away = light.shade(-1.0, 0.0);
+        assert!(towards > light.shade(0.0, 0.0));
+        assert!(away < light.shade(0.0, 0.0));
+
+        let exaggerated = Light { exaggeration: 4.0, ..light };
+        assert!(exaggerated.shade(-1.0, 0.0) < away);
+    }
+}
@@ -11,6 +11,7 @@ pub mod tileshape;
 
 use std::{collections::BTreeMap, fs};
 
+use clap::ArgEnum;
 use image::{GenericImageView, ImageBuffer, Pixel, Rgba, RgbaImage};
 use indicatif::ProgressIterator;
 use itertools::iproduct;
@@ -24,13 +25,15 @@ use crate::definitions::flo::Flo;
 use crate::definitions::mapscenes::MapScene;
 #[cfg(any(feature = "rs3", feature = "osrs"))]
 use crate::definitions::{overlays::Overlay, underlays::Underlay};
+#[cfg(not(test))]
+use crate::renderers::encode;
 use crate::{
     cache::error::CacheResult,
     cli::Config,
     definitions::{
         location_configs::LocationConfig,
         mapsquares::{GroupMapSquare, GroupMapSquareIterator},
-        sprites::{self, Sprite},
+        sprites::Sprite,
     },
     renderers::{scale, zoom},
     utils::color::Color,
@@ -45,7 +48,10 @@ pub struct RenderConfig {
     pub tile_size: u32,
     /// The highest zoom level.
     pub initial_zoom: i8,
-    /// The range at which underlays are blended.
+    /// The range at which underlays are blended, unless overridden by `--interp`.
+    ///
+    /// The colour of a tile is the average of the `(2 * interp + 1)²` tiles around it,
+    /// so rendering time grows quadratically with it.
     pub interp: isize,
     /// The height and width of a full [`MapSquare`](crate::definitions::mapsquares::MapSquare) in pixels.
     pub dim: u32,
@@ -92,7 +98,9 @@ pub fn render(config: &Config) -> CacheResult<()> {
         fs::create_dir_all(&inner_folder).map_err(|e| CacheError::io(e, inner_folder))?;
     }
 
-    let iter = GroupMapSquareIterator::new(-1_i32..=1_i32, -1_i32..=1_i32, config)?;
+    // Blending reaches into as many neighbouring squares as needed, but at least one for lines and mapscenes.
+    let reach = 1.max((interp(config) as i32 + 63) / 64);
+    let iter = GroupMapSquareIterator::new(-reach..=reach, -reach..=reach, config)?;
 
     inner_render(config, NAME, iter)?;
 
@@ -100,6 +108,11 @@ pub fn render(config: &Config) -> CacheResult<()> {
     Ok(())
 }
 
+/// The range at which underlays are blended.
+fn interp(config: &Config) -> isize {
+    config.interp.map_or(CONFIG.interp, isize::from)
+}
+
 // Separated for use in tests.
 
 fn inner_render(config: &Config, name: &str, iter: GroupMapSquareIterator) -> CacheResult<()> {
@@ -113,19 +126,13 @@ fn inner_render(config: &Config, name: &str, iter: GroupMapSquareIterator) -> Ca
     #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
     let mapscenes = MapScene::dump_all(config)?;
 
-    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
-    let sprites = sprites::dumps(
+    let sprites = mapscenes::load_sprites(
         CONFIG.scale,
-        mapscenes.values().filter_map(|mapscene| mapscene.sprite_id).collect::<Vec<_>>(),
+        #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+        &mapscenes,
         config,
     )?;
 
-    #[cfg(all(feature = "osrs", not(feature = "2009_1_shim")))]
-    let sprites = sprites::dumps(CONFIG.scale, vec![317], config)?; // 317 is the sprite named "mapscene"
-
-    #[cfg(feature = "legacy")]
-    let sprites: BTreeMap<(u32, u32), Sprite> = sprites::get_mapscenes(CONFIG.scale, config)?;
-
     #[cfg(feature = "legacy")]
     let flos = Flo::dump_all(config)?;
 
@@ -168,6 +175,7 @@ pub fn render_tile(
 
         base::put(
             plane,
+            interp(config),
             &mut img,
             &squares,
             #[cfg(any(feature = "rs3", feature = "osrs"))]
@@ -203,39 +211,83 @@ pub fn render_tile(
 
 type Img = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
+/// Describes how the planes of a [`MapSquare`](crate::definitions::mapsquares::MapSquare) are combined into images.
+#[derive(ArgEnum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[clap(rename_all = "snake_case")]
+pub enum PlaneMode {
+    /// Every plane gets its own image, showing the planes below it darkened wherever it is empty.
+    #[default]
+    Stacked,
+    /// Every plane gets its own image, showing only that plane.
+    Separate,
+    /// All planes are drawn into one image, each drawn semi-transparently over the ones below it.
+    ///
+    /// This is saved as plane 0.
+    Composited,
+}
+
+/// Returns `imgs[plane]`, with the planes below it showing through, darkened, where it is transparent.
+fn stack(imgs: &[Img; 4], plane: usize) -> Img {
+    // SAFETY (2) these checks assure that...
+    assert!(plane <= 3);
+    for img in imgs {
+        assert_eq!(img.dimensions(), imgs[0].dimensions());
+    }
+
+    RgbaImage::from_fn(imgs[0].width(), imgs[0].height(), |x, y| {
+        let mut i = (0..=plane).rev();
+
+        loop {
+            // SAFETY (1): this will always be valid....
+            let p = unsafe { i.next().unwrap_unchecked() };
+
+            // SAFETY (2):..these getters are always valid.
+            let pixel = unsafe { imgs.get_unchecked(p).unsafe_get_pixel(x, y) };
+
+            // SAFETY (1): ...as this exit condition always exits the loop if p == 0.
+            if p == 0 || pixel[3] != 0 {
+                break if p == plane {
+                    pixel
+                } else {
+                    pixel.map_without_alpha(|channel| channel / 2)
+                };
+            }
+        }
+    })
+}
+
+/// Draws every plane over the ones below it at half opacity, starting from plane 0.
+fn composite(imgs: &[Img; 4]) -> Img {
+    let mut base = imgs[0].clone();
+    for img in &imgs[1..] {
+        for (lower, upper) in base.pixels_mut().zip(img.pixels()) {
+            if upper[3] != 0 {
+                let mut upper = *upper;
+                upper[3] /= 2;
+                lower.blend(&upper);
+            }
+        }
+    }
+    base
+}
+
 pub fn save_smallest(config: &Config, name: &str, i: u8, j: u8, imgs: [Img; 4]) {
     #![allow(unused_variables)]
 
     let map_id = CONFIG.map_id;
 
-    // SAFETY (2) these checks assure that...
     assert_eq!(CONFIG.dim % 4, 0);
     for img in &imgs {
         assert_eq!(img.dimensions(), (CONFIG.dim, CONFIG.dim));
     }
 
-    for plane in 0..=3 {
-        let base = RgbaImage::from_fn(CONFIG.dim, CONFIG.dim, |x, y| {
-            let mut i = (0..=plane).rev();
-
-            loop {
-                // SAFETY (1): this will always be valid....
-                let p = unsafe { i.next().unwrap_unchecked() };
-
-                // SAFETY (2):..these getters are always valid.
-                let pixel = unsafe { imgs.get_unchecked(p).unsafe_get_pixel(x, y) };
-
-                // SAFETY (1): ...as this exit condition always exits the loop if p == 0.
-                if p == 0 || pixel[3] != 0 {
-                    break if p == plane {
-                        pixel
-                    } else {
-                        pixel.map_without_alpha(|channel| channel / 2)
-                    };
-                }
-            }
-        });
+    let bases: Vec<(usize, Img)> = match config.plane_mode {
+        PlaneMode::Stacked => (0..=3).map(|plane| (plane, stack(&imgs, plane))).collect(),
+        PlaneMode::Separate => imgs.into_iter().enumerate().collect(),
+        PlaneMode::Composited => vec![(0, composite(&imgs))],
+    };
 
+    for (plane, base) in bases {
         if CONFIG.initial_zoom >= 4 {
             let base_i = i as u32 * 4;
             let base_j = j as u32 * 4;
@@ -256,7 +308,7 @@ pub fn save_smallest(config: &Config, name: &str, i: u8, j: u8, imgs: [Img; 4])
                     let xx = base_i + x;
                     let yy = base_j + y;
                     let filename = path!(config.output / name / format!("{map_id}/4/{plane}_{xx}_{yy}.png"));
-                    sub_image.to_image().save(filename).unwrap();
+                    encode::save(&sub_image.to_image(), filename, config).unwrap();
                 }
             }
         }
@@ -283,7 +335,7 @@ pub fn save_smallest(config: &Config, name: &str, i: u8, j: u8, imgs: [Img; 4])
                     let xx = base_i + x;
                     let yy = base_j + y;
                     let filename = path!(config.output / name / format!("{map_id}/3/{plane}_{xx}_{yy}.png"));
-                    resized.save(filename).unwrap();
+                    encode::save(&resized, filename, config).unwrap();
                 }
             }
         }
@@ -302,7 +354,7 @@ pub fn save_smallest(config: &Config, name: &str, i: u8, j: u8, imgs: [Img; 4])
             /* don't save useless tiles */
             {
                 let filename = path!(config.output / name / format!("{map_id}/2/{plane}_{base_i}_{base_j}.png"));
-                resized.save(filename).unwrap();
+                encode::save(&resized, filename, config).unwrap();
             }
         }
     }
@@ -322,6 +374,35 @@ pub fn render_bench() -> CacheResult<()> {
     Ok(())
 }
 
+#[cfg(test)]
+mod plane_tests {
+    use super::*;
+
+    fn planes() -> [Img; 4] {
+        let mut imgs = [(); 4].map(|_| RgbaImage::from_pixel(2, 1, Rgba(Color::ALPHA)));
+        imgs[0].put_pixel(0, 0, Rgba([200, 0, 0, 255]));
+        imgs[0].put_pixel(1, 0, Rgba([200, 0, 0, 255]));
+        imgs[1].put_pixel(1, 0, Rgba([0, 0, 200, 255]));
+        imgs
+    }
+
+    #[test]
+    fn stacked() {
+        let imgs = planes();
+        assert_eq!(stack(&imgs, 1).get_pixel(0, 0), &Rgba([100, 0, 0, 255]));
+        assert_eq!(stack(&imgs, 1).get_pixel(1, 0), &Rgba([0, 0, 200, 255]));
+    }
+
+    #[test]
+    fn composited() {
+        let img = composite(&planes());
+        assert_eq!(img.get_pixel(0, 0), &Rgba([200, 0, 0, 255]));
+
+        let Rgba([red, green, blue, alpha]) = *img.get_pixel(1, 0);
+        assert!(red > 0 && blue > 0 && green == 0 && alpha == 255, "{:?}", img.get_pixel(1, 0));
+    }
+}
+
 #[cfg(all(test, feature = "rs3"))]
 mod map_tests {
     use super::*;
@@ -11,8 +11,11 @@ use crate::definitions::{mapsquares::GroupMapSquare, tiles::Tile};
 use crate::definitions::{overlays::Overlay, underlays::Underlay};
 
 /// Applies ground colouring to the base image.
+///
+/// Underlay colours are averaged over all tiles within `interp` tiles, see [`RenderConfig::interp`](super::RenderConfig::interp).
 pub fn put(
     plane: usize,
+    interp: isize,
     img: &mut RgbaImage,
     squares: &GroupMapSquare,
     #[cfg(any(feature = "rs3", feature = "osrs"))] underlay_definitions: &BTreeMap<u32, Underlay>,
@@ -34,7 +37,9 @@ pub fn put(
                     if condition {
                         // Underlays
                         #[cfg(any(feature = "rs3", feature = "osrs"))]
-                        if let Some([red, green, blue]) = get_underlay_colour(column, underlay_definitions, squares, p, x as usize, y as usize) {
+                        if let Some([red, green, blue]) =
+                            get_underlay_colour(column, underlay_definitions, squares, p, x as usize, y as usize, interp)
+                        {
                             let fill = Rgba([red, green, blue, 255u8]);
 
                             tileshape::draw_underlay(column[p].shape, CONFIG.tile_size, |(a, b)| unsafe {
@@ -115,7 +120,7 @@ pub fn put(
 
                         // Underlays
                         #[cfg(feature = "legacy")]
-                        if let Some([red, green, blue]) = get_underlay_colour(column, flos, squares, p, x as usize, y as usize) {
+                        if let Some([red, green, blue]) = get_underlay_colour(column, flos, squares, p, x as usize, y as usize, interp) {
                             let fill = Rgba([red, green, blue, 255u8]);
 
                             tileshape::draw_underlay(column[p].shape, CONFIG.tile_size, |(a, b)| unsafe {
@@ -193,7 +198,7 @@ pub fn put(
     }
 }
 
-/// Averages out the [`Underlay`] colours, with a range specified by [`INTERP`].
+/// Averages out the [`Underlay`] colours of all tiles within `interp`.
 #[cfg(any(feature = "rs3", feature = "osrs"))]
 fn get_underlay_colour(
     column: ArrayBase<ViewRepr<&Tile>, Dim<[usize; 1]>>,
@@ -202,10 +207,11 @@ fn get_underlay_colour(
     plane: usize,
     x: usize,
     y: usize,
+    interp: isize,
 ) -> Option<[u8; 3]> {
     // only compute a colour average if the tile has a underlay
     column[plane].underlay_id.map(|_| {
-        let tiles = squares.tiles_iter(plane, x, y, CONFIG.interp);
+        let tiles = squares.tiles_iter(plane, x, y, interp);
 
         let underlays = tiles.filter_map(|elem| elem.underlay_id);
 
@@ -230,7 +236,7 @@ fn get_underlay_colour(
     })
 }
 
-/// Averages out the [`Underlay`] colours, with a range specified by [`INTERP`].
+/// Averages out the [`Underlay`] colours of all tiles within `interp`.
 #[cfg(feature = "legacy")]
 fn get_underlay_colour(
     column: ArrayBase<ViewRepr<&Tile>, Dim<[usize; 1]>>,
@@ -239,10 +245,11 @@ fn get_underlay_colour(
     plane: usize,
     x: usize,
     y: usize,
+    interp: isize,
 ) -> Option<[u8; 3]> {
     // only compute a colour average if the tile has a underlay
     column[plane].underlay_id.map(|_| {
-        let tiles = squares.tiles_iter(plane, x, y, CONFIG.interp);
+        let tiles = squares.tiles_iter(plane, x, y, interp);
 
         let underlays = tiles.filter_map(|elem| elem.underlay_id);
 
@@ -258,10 +265,12 @@ fn get_underlay_colour(
             .fold((0, (0, 0, 0)), |(acc_w, (acc_r, acc_g, acc_b)), (w, (r, g, b))| {
                 (acc_w + w, (acc_r + r, acc_g + g, acc_b + b))
             });
+        // Missing underlays count as black.
+        let window = ((2 * interp + 1) * (2 * interp + 1)) as usize;
         [
-            (reds / 121).try_into().unwrap(),
-            (greens / 121).try_into().unwrap(),
-            (blues / 121).try_into().unwrap(),
+            (reds / window).try_into().unwrap(),
+            (greens / window).try_into().unwrap(),
+            (blues / window).try_into().unwrap(),
         ]
     })
 }
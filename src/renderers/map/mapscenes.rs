@@ -1,16 +1,66 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, fs};
 
-use image::{GenericImage, GenericImageView, RgbaImage};
+use image::{GenericImage, GenericImageView, ImageError, RgbaImage};
 use itertools::iproduct;
+use path_macro::path;
+use rs3cache_backend::error::CacheError;
+use serde::Serialize;
 
 #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
 use crate::definitions::mapscenes::MapScene;
 use crate::{
-    definitions::{location_configs::LocationConfig, mapsquares::GroupMapSquare, sprites::Sprite},
+    cache::error::CacheResult,
+    cli::Config,
+    definitions::{
+        location_configs::LocationConfig,
+        locations::Location,
+        mapsquares::GroupMapSquare,
+        sprites::{self, Sprite},
+    },
     renderers::map::CONFIG,
     utils::rangeclamp::RangeClamp,
 };
 
+/// Describes which point of a mapscene sprite is pinned to the north-west corner of the tile its location is on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    /// The sprite hangs down and to the east of the tile corner.
+    ///
+    /// This is how the frames of the "mapscene" sprite are placed,
+    /// as well as sprites of [`MapScene`] configurations that are not [`centred`](MapScene::centred).
+    TopLeft,
+    /// The sprite is raised by half its height, so that its left edge is vertically centred on the tile corner.
+    ///
+    /// Sprites of [`centred`](MapScene::centred) [`MapScene`] configurations are drawn like this.
+    CentreLeft,
+}
+
+impl Anchor {
+    /// The anchor of sprites referenced through [`MapScene`] configurations.
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+    pub const fn of_mapscene(mapscene: &MapScene) -> Self {
+        if mapscene.centred {
+            Self::CentreLeft
+        } else {
+            Self::TopLeft
+        }
+    }
+
+    /// The anchor of the frames of the "mapscene" sprite.
+    #[cfg(any(all(feature = "osrs", not(feature = "2009_1_shim")), feature = "legacy"))]
+    pub const fn of_frame() -> Self {
+        Self::TopLeft
+    }
+
+    /// Returns the pixel offset of the top left corner of a sprite `height` pixels high relative to the tile's top left corner.
+    pub const fn offset(&self, height: u32) -> (i32, i32) {
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::CentreLeft => (0, -(height as i32 / 2)),
+        }
+    }
+}
+
 /// Applies [`MapScene`]s to the base image.
 pub fn put(
     plane: usize,
@@ -24,70 +74,492 @@ pub fn put(
         .all_locations_iter()
         .filter_map(|loc| {
             if loc.plane.matches(&(plane as u8)) {
-                location_config
-                    .get(&(loc.id))
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "fatal error: maps and location_configs are logically inconsistent, tried looking up {} of mapsquare {}_{}",
-                            loc.id,
-                            squares.core_i(),
-                            squares.core_j()
-                        );
-                    })
+                let loc_config = location_config.get(&(loc.id)).unwrap_or_else(|| {
+                    panic!(
+                        "fatal error: maps and location_configs are logically inconsistent, tried looking up {} of mapsquare {}_{}",
+                        loc.id,
+                        squares.core_i(),
+                        squares.core_j()
+                    );
+                });
+                loc_config
                     .mapscene
                     .and_then(|mapscene_id| {
-                        #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
-                        {
-                            mapscenes[&(mapscene_id as u32)]
-                                .sprite_id
-                                // sprites is constructed with ids from
-                                // mapscenes so it should always be in the map.
-                                .map(|sprite_id| (loc, &sprites[&(sprite_id, 0)]))
-                        }
-
-                        #[cfg(all(feature = "osrs", not(feature = "2009_1_shim")))]
-                        {
-                            // 317 is the sprite named "mapscene", whose frames form all the mapscenes.
-                            // 22 is missing and indicates the empty mapscene, which is why this does not index
-                            sprites.get(&(317, mapscene_id as u32)).map(|s| (loc, s))
-                        }
-
-                        #[cfg(feature = "legacy")]
-                        {
-                            sprites.get(&(317, mapscene_id as u32)).map(|s| (loc, s))
-                        }
+                        mapscene_sprite(
+                            mapscene_id as u32,
+                            #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+                            mapscenes,
+                            sprites,
+                        )
                     })
+                    .map(|(sprite, anchor)| (loc, footprint(loc, loc_config), sprite, anchor))
             } else {
                 None
             }
         })
-        .for_each(|(loc, sprite)| {
+        .for_each(|(loc, footprint, sprite, anchor)| {
             let offset_a = CONFIG.tile_size as i32 * ((loc.i as i32 - squares.core_i() as i32) * 64 + loc.x as i32);
             let offset_b = CONFIG.tile_size as i32 * (63 - (loc.j as i32 - squares.core_j() as i32) * 64 - loc.y as i32);
 
-            let dim_a = sprite.width() as i32;
-            let dim_b = sprite.height() as i32;
-
-            // There is an offset here that's not present in osrs
-            let vertical_offset = if cfg!(feature = "rs3") { dim_b / 2 } else { 0 };
-
-            let range_a = (offset_a..(offset_a + dim_a)).clamp(0, img.width() as i32);
-            let range_b = ((offset_b - vertical_offset)..(offset_b + dim_b - vertical_offset)).clamp(0, img.height() as i32);
-
-            for (a, b) in iproduct!(range_a, range_b) {
-                let sprite_a = (a - offset_a) as u32;
-                let sprite_b = (b - (offset_b - vertical_offset)) as u32;
-
-                let sprite_pixel = unsafe {
-                    debug_assert!(sprite_a < sprite.width() && sprite_b < sprite.height(), "Index out of range.");
-                    sprite.unsafe_get_pixel(sprite_a, sprite_b)
-                };
-                if sprite_pixel[3] != 0 {
-                    unsafe {
-                        debug_assert!((a as u32) < img.width() && (b as u32) < img.height(), "Index out of range.");
-                        img.unsafe_put_pixel(a as u32, b as u32, sprite_pixel)
-                    };
-                }
-            }
+            let (anchor_a, anchor_b) = anchor.offset(sprite.height());
+            stamp_footprint(img, sprite, offset_a + anchor_a, offset_b + anchor_b, footprint, CONFIG.tile_size);
         });
 }
+
+/// Loads the sprites of all mapscenes, scaled by `scale`, to be looked up with [`mapscene_sprite`].
+pub fn load_sprites(
+    scale: u32,
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))] mapscenes: &BTreeMap<u32, MapScene>,
+    config: &Config,
+) -> CacheResult<BTreeMap<(u32, u32), Sprite>> {
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+    {
+        sprites::dumps(scale, mapscenes.values().filter_map(|mapscene| mapscene.sprite_id).collect(), config)
+    }
+
+    // 317 is the sprite named "mapscene", whose frames form all the mapscenes.
+    #[cfg(all(feature = "osrs", not(feature = "2009_1_shim")))]
+    {
+        sprites::dumps(scale, vec![317], config)
+    }
+
+    #[cfg(feature = "legacy")]
+    {
+        sprites::get_mapscenes(scale, config)
+    }
+}
+
+/// Looks up the sprite drawn for `mapscene_id`, and how it is anchored, in sprites returned by [`load_sprites`].
+///
+/// Returns `None` if the mapscene does not exist or has no sprite.
+// The lifetime can only be elided in builds without `mapscenes`.
+#[allow(clippy::needless_lifetimes)]
+pub fn mapscene_sprite<'s>(
+    mapscene_id: u32,
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))] mapscenes: &BTreeMap<u32, MapScene>,
+    sprites: &'s BTreeMap<(u32, u32), Sprite>,
+) -> Option<(&'s Sprite, Anchor)> {
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+    {
+        let mapscene = mapscenes.get(&mapscene_id)?;
+        mapscene
+            .sprite_id
+            // sprites is constructed with ids from
+            // mapscenes so it should always be in the map.
+            .map(|sprite_id| (&sprites[&(sprite_id, 0)], Anchor::of_mapscene(mapscene)))
+    }
+
+    // 22 is missing and indicates the empty mapscene, which is why this does not index
+    #[cfg(any(all(feature = "osrs", not(feature = "2009_1_shim")), feature = "legacy"))]
+    {
+        sprites.get(&(317, mapscene_id)).map(|s| (s, Anchor::of_frame()))
+    }
+}
+
+/// Looks up the icon of `mapscene_id` in sprites returned by [`load_sprites`], for example to draw a legend.
+///
+/// Use [`mapscene_sprite`] to also get where the icon is anchored when drawing it on the map.
+// The lifetime can only be elided in builds without `mapscenes`.
+#[allow(clippy::needless_lifetimes)]
+pub fn resolve_mapscene_icon<'s>(
+    mapscene_id: u32,
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))] mapscenes: &BTreeMap<u32, MapScene>,
+    sprites: &'s BTreeMap<(u32, u32), Sprite>,
+) -> Option<&'s Sprite> {
+    mapscene_sprite(
+        mapscene_id,
+        #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+        mapscenes,
+        sprites,
+    )
+    .map(|(sprite, _)| sprite)
+}
+
+/// The position of a sprite in an atlas made by [`pack_atlas`], in pixels.
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AtlasEntry {
+    /// The horizontal position of the sprite's left edge.
+    pub x: u32,
+    /// The vertical position of the sprite's top edge.
+    pub y: u32,
+    /// The width of the sprite.
+    pub w: u32,
+    /// The height of the sprite.
+    pub h: u32,
+}
+
+/// Packs `sprites` into a single image, placing them in rows from tallest to shortest.
+///
+/// Returns the image and where each sprite was placed in it.
+/// Without any sprites the image is a single transparent pixel, as an empty image cannot be saved.
+pub fn pack_atlas<K: Ord + Copy>(sprites: &BTreeMap<K, &Sprite>) -> (RgbaImage, BTreeMap<K, AtlasEntry>) {
+    let area: u32 = sprites.values().map(|sprite| sprite.width() * sprite.height()).sum();
+    let widest = sprites.values().map(|sprite| sprite.width()).max().unwrap_or(0);
+    let width = widest.max((area as f64).sqrt().ceil() as u32);
+
+    let mut order: Vec<_> = sprites.iter().collect();
+    order.sort_by_key(|(_, sprite)| std::cmp::Reverse(sprite.height()));
+
+    let mut entries = BTreeMap::new();
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for (key, sprite) in order {
+        if x + sprite.width() > width {
+            (x, y, row_height) = (0, y + row_height, 0);
+        }
+        let entry = AtlasEntry {
+            x,
+            y,
+            w: sprite.width(),
+            h: sprite.height(),
+        };
+        entries.insert(*key, entry);
+        x += sprite.width();
+        row_height = row_height.max(sprite.height());
+    }
+
+    let mut img = RgbaImage::new(width.max(1), (y + row_height).max(1));
+    for (key, entry) in &entries {
+        img.copy_from(sprites[key], entry.x, entry.y)
+            .expect("the atlas is large enough to hold every sprite");
+    }
+    (img, entries)
+}
+
+/// Saves the sprites of all mapscenes as a single image `mapscenes.png`,
+/// with the position of each mapscene's sprite saved to `mapscenes.json`. Exposed as `--render mapscene_atlas`.
+pub fn export_atlas(config: &Config) -> CacheResult<()> {
+    fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.to_path_buf()))?;
+
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+    let mapscenes = MapScene::dump_all(config)?;
+    let sprites = load_sprites(
+        1,
+        #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+        &mapscenes,
+        config,
+    )?;
+
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+    let ids: Vec<u32> = mapscenes.keys().copied().collect();
+    #[cfg(any(all(feature = "osrs", not(feature = "2009_1_shim")), feature = "legacy"))]
+    let ids: Vec<u32> = sprites.keys().map(|(_, frame)| *frame).collect();
+
+    let icons: BTreeMap<u32, &Sprite> = ids
+        .into_iter()
+        .filter_map(|id| {
+            let sprite = resolve_mapscene_icon(
+                id,
+                #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+                &mapscenes,
+                &sprites,
+            )?;
+            Some((id, sprite))
+        })
+        .collect();
+
+    let (img, entries) = pack_atlas(&icons);
+
+    let path = path!(config.output / "mapscenes.png");
+    match img.save(&path) {
+        Ok(()) => {}
+        Err(ImageError::IoError(e)) => return Err(CacheError::io(e, path)),
+        Err(other) => return Err(CacheError::image(other, path)),
+    }
+    crate::cli::write_serialized(path!(config.output / "mapscenes.json"), &entries, config)
+}
+
+/// Returns the number of tiles a location occupies to the east and to the north of its own tile.
+fn footprint(loc: &Location, config: &LocationConfig) -> (u32, u32) {
+    let dim_x = config.dim_x.unwrap_or(1).max(1) as u32;
+    let dim_y = config.dim_y.unwrap_or(1).max(1) as u32;
+
+    // A quarter turn swaps the dimensions.
+    if loc.rotation & 0x1 == 0 {
+        (dim_x, dim_y)
+    } else {
+        (dim_y, dim_x)
+    }
+}
+
+/// Stamps `sprite` repeatedly across a footprint of `size_x` by `size_y` tiles,
+/// where (`offset_a`, `offset_b`) is the position of the sprite on the south-western tile.
+///
+/// Copies are spaced a whole number of tiles apart such that they do not overlap,
+/// so a sprite that is larger than the footprint is stamped only once.
+fn stamp_footprint(img: &mut RgbaImage, sprite: &Sprite, offset_a: i32, offset_b: i32, (size_x, size_y): (u32, u32), tile_size: u32) {
+    let step_x = ((sprite.width() + tile_size - 1) / tile_size).max(1) as usize;
+    let step_y = ((sprite.height() + tile_size - 1) / tile_size).max(1) as usize;
+
+    for (dx, dy) in iproduct!((0..size_x).step_by(step_x), (0..size_y).step_by(step_y)) {
+        stamp(img, sprite, offset_a + (dx * tile_size) as i32, offset_b - (dy * tile_size) as i32);
+    }
+}
+
+/// Draws the opaque pixels of `sprite` onto `img`, with its top left corner at (`offset_a`, `offset_b`).
+///
+/// Any part of the sprite outside of `img` is cut off.
+fn stamp(img: &mut RgbaImage, sprite: &Sprite, offset_a: i32, offset_b: i32) {
+    let dim_a = sprite.width() as i32;
+    let dim_b = sprite.height() as i32;
+
+    let range_a = (offset_a..(offset_a + dim_a)).clamp(0, img.width() as i32);
+    let range_b = (offset_b..(offset_b + dim_b)).clamp(0, img.height() as i32);
+
+    for (a, b) in iproduct!(range_a, range_b) {
+        let sprite_a = (a - offset_a) as u32;
+        let sprite_b = (b - offset_b) as u32;
+
+        let sprite_pixel = unsafe {
+            debug_assert!(sprite_a < sprite.width() && sprite_b < sprite.height(), "Index out of range.");
+            sprite.unsafe_get_pixel(sprite_a, sprite_b)
+        };
+        if sprite_pixel[3] != 0 {
+            unsafe {
+                debug_assert!((a as u32) < img.width() && (b as u32) < img.height(), "Index out of range.");
+                img.unsafe_put_pixel(a as u32, b as u32, sprite_pixel)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod atlas_tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn no_overlap() {
+        let sprites: Vec<Sprite> = (1..=6).map(|n| Sprite::from_pixel(n * 3, 10 - n, Rgba([n as u8, 0, 0, 255]))).collect();
+        let refs: BTreeMap<u32, &Sprite> = sprites.iter().enumerate().map(|(n, sprite)| (n as u32, sprite)).collect();
+
+        let (img, entries) = pack_atlas(&refs);
+        assert_eq!(entries.len(), sprites.len());
+        for (id, entry) in &entries {
+            let sprite = refs[id];
+            assert_eq!((entry.w, entry.h), sprite.dimensions());
+            assert!(entry.x + entry.w <= img.width() && entry.y + entry.h <= img.height());
+            // Every sprite is intact, so none were drawn over.
+            assert_eq!(&img.view(entry.x, entry.y, entry.w, entry.h).to_image(), sprite);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let (img, entries) = pack_atlas::<u32>(&BTreeMap::new());
+        assert!(entries.is_empty());
+        assert_eq!(img.dimensions(), (1, 1));
+
+        let path = std::env::temp_dir().join("rs3cache_empty_atlas.png");
+        img.save(&path).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod anchor_tests {
+    use image::Rgba;
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const EMPTY: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    // Stamps a 2x2 sprite on the tile at x = 3, y = 60 of a single mapsquare.
+    fn stamp_at(anchor: Anchor) -> RgbaImage {
+        let tile_size = 4;
+        let mut img = RgbaImage::from_pixel(64 * tile_size, 64 * tile_size, EMPTY);
+        let sprite = Sprite::from_pixel(2, 2, RED);
+
+        let offset_a = tile_size as i32 * 3;
+        let offset_b = tile_size as i32 * (63 - 60);
+        let (anchor_a, anchor_b) = anchor.offset(sprite.height());
+        stamp(&mut img, &sprite, offset_a + anchor_a, offset_b + anchor_b);
+        img
+    }
+
+    #[test]
+    fn top_left() {
+        let img = stamp_at(Anchor::TopLeft);
+        for (a, b) in iproduct!(12..14, 12..14) {
+            assert_eq!(img.get_pixel(a, b), &RED, "{a}, {b}");
+        }
+        assert_eq!(img.get_pixel(12, 11), &EMPTY);
+        assert_eq!(img.get_pixel(12, 14), &EMPTY);
+        assert_eq!(img.pixels().filter(|p| **p == RED).count(), 4);
+    }
+
+    #[test]
+    fn centre_left() {
+        let img = stamp_at(Anchor::CentreLeft);
+        for (a, b) in iproduct!(12..14, 11..13) {
+            assert_eq!(img.get_pixel(a, b), &RED, "{a}, {b}");
+        }
+        assert_eq!(img.get_pixel(12, 13), &EMPTY);
+        assert_eq!(img.pixels().filter(|p| **p == RED).count(), 4);
+    }
+
+    #[test]
+    fn clipped() {
+        let mut img = RgbaImage::from_pixel(4, 4, EMPTY);
+        let sprite = Sprite::from_pixel(2, 2, RED);
+        stamp(&mut img, &sprite, -1, 3);
+        assert_eq!(img.get_pixel(0, 3), &RED);
+        assert_eq!(img.pixels().filter(|p| **p == RED).count(), 1);
+    }
+
+    #[test]
+    fn multi_tile() {
+        let tile_size = 4;
+        let mut img = RgbaImage::from_pixel(64 * tile_size, 64 * tile_size, EMPTY);
+        let sprite = Sprite::from_pixel(tile_size, tile_size, RED);
+
+        // A 3x2 location, turned a quarter, on the tile at x = 3, y = 60.
+        let config = LocationConfig {
+            dim_x: Some(3),
+            dim_y: Some(2),
+            ..Default::default()
+        };
+        let loc = Location {
+            plane: crate::definitions::locations::Watery::False(0),
+            i: 0,
+            j: 0,
+            x: 3,
+            y: 60,
+            id: 0,
+            r#type: 10,
+            rotation: 1,
+        };
+        assert_eq!(footprint(&loc, &config), (2, 3));
+
+        stamp_footprint(&mut img, &sprite, 12, 12, footprint(&loc, &config), tile_size);
+
+        // It covers x = 3..5, y = 60..63.
+        for (a, b) in iproduct!(12..20, 4..16) {
+            assert_eq!(img.get_pixel(a, b), &RED, "{a}, {b}");
+        }
+        assert_eq!(img.pixels().filter(|p| **p == RED).count(), 8 * 12);
+    }
+
+    #[test]
+    fn large_sprite_is_stamped_once() {
+        let tile_size = 4;
+        let mut img = RgbaImage::from_pixel(64 * tile_size, 64 * tile_size, EMPTY);
+        let sprite = Sprite::from_pixel(3 * tile_size, 3 * tile_size, RED);
+
+        stamp_footprint(&mut img, &sprite, 12, 12, (2, 2), tile_size);
+        assert_eq!(img.pixels().filter(|p| **p == RED).count(), 9 * 16);
+    }
+
+    // Draws mapscene 5 for a location on the tile at x = 3, y = 60 of square 50_50.
+    fn put_mapscene(#[allow(unused_variables)] centred: bool) -> RgbaImage {
+        let tile_size = CONFIG.tile_size;
+        let mut img = RgbaImage::from_pixel(64 * tile_size, 64 * tile_size, EMPTY);
+
+        let loc = Location {
+            plane: crate::definitions::locations::Watery::False(0),
+            i: 50,
+            j: 50,
+            x: 3,
+            y: 60,
+            id: 1,
+            r#type: 22,
+            rotation: 0,
+        };
+        let squares = GroupMapSquare::from_locations(50, 50, vec![loc]);
+        let location_configs = BTreeMap::from([(
+            1,
+            LocationConfig {
+                mapscene: Some(5),
+                ..Default::default()
+            },
+        )]);
+        let sprite = Sprite::from_pixel(tile_size, tile_size, RED);
+
+        #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+        let (mapscenes, sprites) = {
+            let mut mapscene = MapScene::default();
+            mapscene.id = 5;
+            mapscene.sprite_id = Some(7);
+            mapscene.centred = centred;
+            (BTreeMap::from([(5, mapscene)]), BTreeMap::from([((7, 0), sprite)]))
+        };
+        #[cfg(any(all(feature = "osrs", not(feature = "2009_1_shim")), feature = "legacy"))]
+        let sprites = BTreeMap::from([((317, 5), sprite)]);
+
+        put(
+            0,
+            &mut img,
+            &squares,
+            &location_configs,
+            #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+            &mapscenes,
+            &sprites,
+        );
+        img
+    }
+
+    #[test]
+    fn put_top_left() {
+        let tile_size = CONFIG.tile_size;
+        let img = put_mapscene(false);
+
+        // The tile at y = 60 is the fourth row of tiles from the top.
+        for (a, b) in iproduct!(3 * tile_size..4 * tile_size, 3 * tile_size..4 * tile_size) {
+            assert_eq!(img.get_pixel(a, b), &RED, "{a}, {b}");
+        }
+        assert_eq!(img.pixels().filter(|p| **p == RED).count() as u32, tile_size * tile_size);
+    }
+
+    #[cfg(any(feature = "rs3", feature = "2009_1_shim"))]
+    #[test]
+    fn put_centre_left() {
+        let tile_size = CONFIG.tile_size;
+        let img = put_mapscene(true);
+
+        let top = 3 * tile_size - tile_size / 2;
+        for (a, b) in iproduct!(3 * tile_size..4 * tile_size, top..top + tile_size) {
+            assert_eq!(img.get_pixel(a, b), &RED, "{a}, {b}");
+        }
+        assert_eq!(img.pixels().filter(|p| **p == RED).count() as u32, tile_size * tile_size);
+    }
+
+    // Every mapscene of lumbridge (square 50_50) in a 2009 cache is drawn as if it hangs down from the corner of its tile.
+    #[cfg(feature = "2009_1_shim")]
+    #[test]
+    fn real_mapscenes_hang_from_corner() -> CacheResult<()> {
+        use crate::definitions::mapsquares::MapSquares;
+
+        let config = Config::env();
+        let tile_size = CONFIG.tile_size;
+        let location_configs = LocationConfig::dump_all(&config)?;
+        let mapscenes = MapScene::dump_all(&config)?;
+        let sprites = load_sprites(1, &mapscenes, &config)?;
+
+        let mut checked = 0;
+        for loc in MapSquares::new(&config)?.get(50, 50)?.take_locations()? {
+            let loc_config = &location_configs[&loc.id];
+            let Some((sprite, anchor)) = loc_config.mapscene.and_then(|id| mapscene_sprite(id as u32, &mapscenes, &sprites)) else {
+                continue;
+            };
+            if !loc.plane.matches(&0) {
+                continue;
+            }
+            assert_eq!(anchor, Anchor::TopLeft, "{loc:?}");
+
+            let mut expected = RgbaImage::new(64 * tile_size, 64 * tile_size);
+            let offset_a = (tile_size * loc.x as u32) as i32;
+            let offset_b = (tile_size * (63 - loc.y as u32)) as i32;
+            stamp_footprint(&mut expected, sprite, offset_a, offset_b, footprint(&loc, loc_config), tile_size);
+
+            let mut img = RgbaImage::new(64 * tile_size, 64 * tile_size);
+            let squares = GroupMapSquare::from_locations(50, 50, vec![loc]);
+            put(0, &mut img, &squares, &location_configs, &mapscenes, &sprites);
+
+            assert!(img == expected, "{loc:?}");
+            checked += 1;
+        }
+        assert_ne!(checked, 0);
+        Ok(())
+    }
+}
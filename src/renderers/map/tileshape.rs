@@ -1,3 +1,7 @@
+/// Calls `fun` with every pixel of a `size` by `size` tile that is covered by its overlay.
+///
+/// `shape` is the raw shape of the [`Tile`](crate::definitions::tiles::Tile), see [`TileShape`](crate::definitions::tiles::TileShape).
+/// Its lowest two bits are the rotation, so each shape is drawn in all four orientations.
 pub fn draw_overlay(shape: u8, size: u32, fun: impl FnMut((u32, u32))) {
     debug_assert!(size.is_power_of_two(), "{size} is an invalid size, only 2^n values are allowed.");
 
@@ -68,6 +72,9 @@ pub fn draw_overlay(shape: u8, size: u32, fun: impl FnMut((u32, u32))) {
     };
 }
 
+/// Calls `fun` with every pixel of a `size` by `size` tile that is not covered by its overlay, see [`draw_overlay`].
+///
+/// Tiles without a shape are covered entirely by their underlay.
 pub fn draw_underlay(shape: Option<u8>, size: u32, fun: impl FnMut((u32, u32))) {
     debug_assert!(size.is_power_of_two(), "{size} is an invalid size, only 2^n values are allowed.");
 
@@ -215,4 +222,28 @@ mod shape_tests {
             }
         }
     }
+
+    /// Draws a tile as text, with `#` for overlay and `.` for underlay, one row per line.
+    fn draw(shape: u8, size: u32) -> String {
+        let mut grid = vec![vec!['?'; size as usize]; size as usize];
+        draw_overlay(shape, size, |(x, y)| grid[y as usize][x as usize] = '#');
+        draw_underlay(Some(shape), size, |(x, y)| grid[y as usize][x as usize] = '.');
+        grid.into_iter()
+            .map(|row| row.into_iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // A coastline along a diagonal, with water as the overlay.
+    #[test]
+    fn diagonal_coastline() {
+        assert_eq!(draw(4, 4), "#...\n##..\n###.\n####");
+        assert_eq!(draw(6, 4), ".###\n..##\n...#\n....");
+    }
+
+    #[test]
+    fn half() {
+        assert_eq!(draw(24, 4), "##..\n##..\n##..\n##..");
+        assert_eq!(draw(25, 4), "####\n####\n....\n....");
+    }
 }
@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, fs};
+
+use image::{ImageError, Rgba, RgbaImage};
+use itertools::iproduct;
+use path_macro::path;
+use rs3cache_backend::error::CacheError;
+use rs3cache_utils::color::Color;
+
+use crate::{
+    cache::error::CacheResult,
+    cli::Config,
+    definitions::{overlays::Overlay, underlays::Underlay},
+};
+
+/// The width and height of a swatch, in pixels.
+const SWATCH: u32 = 32;
+
+/// The number of swatches in a row.
+const COLUMNS: u32 = 16;
+
+/// The scale at which labels are drawn.
+const GLYPH_SCALE: u32 = 2;
+
+/// A 3x5 bitmap font for the digits `0..=9`. Each row is three bits, the most significant bit being the leftmost pixel.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// A swatch in the palette.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Swatch {
+    /// A single colour.
+    Plain(u32, [u8; 3]),
+    /// A primary colour, with a secondary colour in the bottom half.
+    Split(u32, [u8; 3], [u8; 3]),
+    /// A configuration without colour.
+    None(u32),
+}
+
+impl Swatch {
+    fn id(&self) -> u32 {
+        match self {
+            Swatch::Plain(id, _) | Swatch::Split(id, ..) | Swatch::None(id) => *id,
+        }
+    }
+}
+
+/// Renders every [`Underlay`] and [`Overlay`] colour as a labeled swatch, and saves them as `palette.png`.
+///
+/// Underlays come first, followed by an empty row and then the overlays.
+/// Overlays with a secondary colour have it drawn in the bottom half of their swatch.
+/// Configurations without a colour are drawn as a grey tile with a red cross.
+pub fn export_palette(config: &Config) -> CacheResult<()> {
+    fs::create_dir_all(&config.output).map_err(|e| CacheError::io(e, config.output.to_path_buf()))?;
+
+    let img = render_palette(&Underlay::dump_all(config)?, &Overlay::dump_all(config)?);
+
+    let path = path!(config.output / "palette.png");
+    match img.save(&path) {
+        Ok(()) => Ok(()),
+        Err(ImageError::IoError(e)) => Err(CacheError::io(e, path)),
+        Err(other) => Err(CacheError::image(other, path)),
+    }
+}
+
+fn render_palette(underlays: &BTreeMap<u32, Underlay>, overlays: &BTreeMap<u32, Overlay>) -> RgbaImage {
+    let underlays: Vec<Swatch> = underlays
+        .values()
+        .map(|underlay| match underlay.colour {
+            Some(colour) => Swatch::Plain(underlay.id, colour),
+            None => Swatch::None(underlay.id),
+        })
+        .collect();
+
+    let overlays: Vec<Swatch> = overlays
+        .values()
+        .map(|overlay| match (overlay.primary_colour, overlay.secondary_colour) {
+            (Some(primary), Some(secondary)) => Swatch::Split(overlay.id, primary, secondary),
+            (Some(colour), None) | (None, Some(colour)) => Swatch::Plain(overlay.id, colour),
+            (None, None) => Swatch::None(overlay.id),
+        })
+        .collect();
+
+    let underlay_rows = (underlays.len() as u32 + COLUMNS - 1) / COLUMNS;
+    let overlay_rows = (overlays.len() as u32 + COLUMNS - 1) / COLUMNS;
+
+    let mut img = RgbaImage::from_pixel(COLUMNS * SWATCH, (underlay_rows + 1 + overlay_rows) * SWATCH, Rgba(Color::ALPHA));
+
+    for (n, swatch) in underlays.iter().enumerate() {
+        let n = n as u32;
+        draw_swatch(&mut img, (n % COLUMNS) * SWATCH, (n / COLUMNS) * SWATCH, swatch);
+    }
+
+    for (n, swatch) in overlays.iter().enumerate() {
+        let n = n as u32;
+        draw_swatch(&mut img, (n % COLUMNS) * SWATCH, (underlay_rows + 1 + n / COLUMNS) * SWATCH, swatch);
+    }
+
+    img
+}
+
+fn draw_swatch(img: &mut RgbaImage, left: u32, top: u32, swatch: &Swatch) {
+    for (a, b) in iproduct!(0..SWATCH, 0..SWATCH) {
+        let pixel = match swatch {
+            Swatch::Plain(_, [red, green, blue]) => [*red, *green, *blue, 255],
+            Swatch::Split(_, [red, green, blue], _) if b < SWATCH / 2 => [*red, *green, *blue, 255],
+            Swatch::Split(_, _, [red, green, blue]) => [*red, *green, *blue, 255],
+            Swatch::None(_) if a == b || a + b == SWATCH - 1 => Color::PURE_RED,
+            Swatch::None(_) => Color::GREY,
+        };
+        img.put_pixel(left + a, top + b, Rgba(pixel));
+    }
+
+    let label = match swatch {
+        Swatch::Plain(_, colour) | Swatch::Split(_, colour, _) if is_light(*colour) => Color::BLACK,
+        _ => Color::WHITE,
+    };
+    draw_number(img, left + 1, top + 1, swatch.id(), Rgba(label));
+}
+
+fn is_light([red, green, blue]: [u8; 3]) -> bool {
+    // Rec. 601 luma
+    299 * red as u32 + 587 * green as u32 + 114 * blue as u32 > 128_000
+}
+
+fn draw_number(img: &mut RgbaImage, left: u32, top: u32, number: u32, colour: Rgba<u8>) {
+    for (n, digit) in number.to_string().bytes().enumerate() {
+        let glyph = DIGITS[(digit - b'0') as usize];
+        let offset = left + n as u32 * 4 * GLYPH_SCALE;
+
+        for (row, bits) in glyph.iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    for (da, db) in iproduct!(0..GLYPH_SCALE, 0..GLYPH_SCALE) {
+                        let a = offset + column * GLYPH_SCALE + da;
+                        let b = top + row as u32 * GLYPH_SCALE + db;
+                        if a < img.width() && b < img.height() {
+                            img.put_pixel(a, b, colour);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
+
+    #[test]
+    fn none_marker() {
+        let mut img = RgbaImage::from_pixel(SWATCH, SWATCH, Rgba(Color::ALPHA));
+        draw_swatch(&mut img, 0, 0, &Swatch::None(7));
+        assert_eq!(img.get_pixel(SWATCH - 1, SWATCH - 1), &Rgba(Color::PURE_RED));
+        assert_eq!(img.get_pixel(SWATCH / 2, SWATCH - 1), &Rgba(Color::GREY));
+    }
+
+    #[test]
+    fn split() {
+        let mut img = RgbaImage::from_pixel(SWATCH, SWATCH, Rgba(Color::ALPHA));
+        draw_swatch(&mut img, 0, 0, &Swatch::Split(1, [10, 20, 30], [40, 50, 60]));
+        assert_eq!(img.get_pixel(SWATCH - 1, 0), &Rgba([10, 20, 30, 255]));
+        assert_eq!(img.get_pixel(SWATCH - 1, SWATCH - 1), &Rgba([40, 50, 60, 255]));
+    }
+
+    #[test]
+    fn label_fits() {
+        let mut img = RgbaImage::from_pixel(SWATCH, SWATCH, Rgba(Color::ALPHA));
+        draw_number(&mut img, 1, 1, 888, Rgba(Color::BLACK));
+        // The rightmost column of the last 8 ends within the swatch.
+        assert_eq!(img.get_pixel(1 + 2 * 4 * GLYPH_SCALE + 3 * GLYPH_SCALE - 1, 1), &Rgba(Color::BLACK));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, ffi::OsString, fs, io, ops::Range, path::Path, sync::LazyLock};
+use std::{collections::HashSet, ffi::OsString, fs, io, ops::Range, sync::LazyLock};
 
 use image::{imageops, ImageBuffer, ImageError, Rgba, RgbaImage};
 use path_macro::path;
@@ -7,9 +7,13 @@ use regex::Regex;
 use rs3cache_backend::error::CacheError;
 use rs3cache_utils::bar::Render;
 
-use crate::{cache::error::CacheResult, cli::Config, renderers::scale};
+use crate::{
+    cache::error::CacheResult,
+    cli::Config,
+    renderers::{encode, scale},
+};
 
-static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?P<p>\d+)(?:_)(?P<i>\d+)(?:_)(?P<j>\d+)(?:\.png)").expect("Regex is cursed."));
+static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?P<p>\d+)(?:_)(?P<i>\d+)(?:_)(?P<j>\d+)(?:\.\w+)").expect("Regex is cursed."));
 
 /// Given a folder and a range of zoom levels, recursively creates tiles for all zoom levels.
 pub fn render_zoom_levels(config: &Config, name: &str, mapid: i32, range: Range<i8>, backfill: [u8; 4]) -> CacheResult<()> {
@@ -21,15 +25,10 @@ pub fn render_zoom_levels(config: &Config, name: &str, mapid: i32, range: Range<
         let new_tile_coordinates = get_future_filenames(config, name, mapid, zoom + 1)?.into_iter();
 
         let func = |((p, i, j), _)| {
-            let img = make_tile(&config.output, name, mapid, zoom, p, i, j, backfill)?;
-            let filename = path!(config.output / &name / format!("{mapid}/{zoom}/{p}_{i}_{j}.png"));
-
-            match img.save(&filename) {
-                Ok(()) => {}
-                Err(ImageError::IoError(e)) => return Err(CacheError::io(e, filename)),
-                Err(other) => panic!("{other}"),
-            };
-            Ok(())
+            let img = make_tile(config, name, mapid, zoom, p, i, j, backfill)?;
+            let filename = path!(config.output / &name / format!("{mapid}/{zoom}/{p}_{i}_{j}"));
+
+            encode::save(&img, filename, config)
         };
 
         new_tile_coordinates.render(format!("{name} zoom level {zoom}")).try_for_each(func)?;
@@ -46,7 +45,7 @@ fn to_coordinates(text: OsString) -> (i32, i32, i32) {
 }
 
 fn make_tile(
-    folder: impl AsRef<Path>,
+    config: &Config,
     name: &str,
     mapid: i32,
     target_zoom: i8,
@@ -57,7 +56,7 @@ fn make_tile(
 ) -> CacheResult<ImageBuffer<Rgba<u8>, Vec<u8>>> {
     let mut base = RgbaImage::from_fn(512, 512, |_, _| Rgba(backfill));
 
-    let files = get_files(folder, name, mapid, target_zoom, target_plane, target_i, target_j);
+    let files = get_files(config, name, mapid, target_zoom, target_plane, target_i, target_j);
 
     for ((di, dj), img) in files {
         match img {
@@ -75,7 +74,7 @@ fn make_tile(
 }
 
 fn get_files(
-    folder: impl AsRef<Path>,
+    config: &Config,
     name: &str,
     mapid: i32,
     target_zoom: i8,
@@ -87,7 +86,8 @@ fn get_files(
         let i = (target_i << 1) + di;
         let j = (target_j << 1) + dj;
         let zoom = target_zoom + 1;
-        let filename = path!(folder / name / format!("{mapid}/{zoom}/{target_plane}_{i}_{j}.png"));
+        let extension = config.image_format.extension();
+        let filename = path!(config.output / name / format!("{mapid}/{zoom}/{target_plane}_{i}_{j}.{extension}"));
         ((di, dj), image::open(filename))
     })
 }
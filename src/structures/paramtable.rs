@@ -4,7 +4,7 @@ use bytes::{Buf, Bytes};
 #[cfg(feature = "pyo3")]
 use pyo3::{exceptions::PyKeyError, prelude::*};
 use rs3cache_backend::buf::JString;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::cache::buf::BufExtra;
 
@@ -13,7 +13,7 @@ use crate::cache::buf::BufExtra;
 /// [`NpcConfig`](crate::definitions::npc_configs::NpcConfig)s can have additional mapping of keys to properties.
 
 #[cfg_attr(feature = "pyo3", pyclass(frozen, get_all))]
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ParamTable {
     /// Key:Value pairs of additional properties.
     pub params: BTreeMap<u32, Param>,
@@ -54,11 +54,13 @@ impl ParamTable {
 }
 
 /// An additional key:property mapping.
-#[derive(Serialize, Debug, Eq, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
 #[serde(untagged)]
 pub enum Param {
     /// The integer variant.
     Integer(i32),
+    /// The long variant. Only [`DbRow`](crate::definitions::dbrows::DbRow)s have these.
+    Long(i64),
     /// The string variant.
     String(JString<Bytes>),
 }
@@ -68,6 +70,7 @@ impl IntoPy<PyObject> for Param {
     fn into_py(self, py: Python) -> PyObject {
         match self {
             Param::Integer(val) => val.into_py(py),
+            Param::Long(val) => val.into_py(py),
             Param::String(val) => val.into_py(py),
         }
     }
@@ -78,7 +81,23 @@ impl IntoPy<PyObject> for &Param {
     fn into_py(self, py: Python) -> PyObject {
         match self {
             Param::Integer(val) => val.into_py(py),
+            Param::Long(val) => val.into_py(py),
             Param::String(val) => val.as_ref().into_py(py),
         }
     }
 }
+
+#[cfg(test)]
+mod paramtable_tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let table = ParamTable {
+            params: BTreeMap::from([(1, Param::Integer(-5)), (2, Param::String(String::from("Swamp").into()))]),
+        };
+        let json = serde_json::to_string(&table).unwrap();
+        let parsed: ParamTable = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.params, table.params);
+    }
+}